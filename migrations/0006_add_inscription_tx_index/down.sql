@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_inscriptions_height;
+DROP INDEX IF EXISTS idx_inscriptions_spent_height;
+
+ALTER TABLE inscriptions DROP COLUMN tx_index;
+ALTER TABLE inscriptions DROP COLUMN spent_tx_index;
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE inscriptions ADD COLUMN tx_index BIGINT;
+ALTER TABLE inscriptions ADD COLUMN spent_tx_index BIGINT;
+
+CREATE INDEX idx_inscriptions_height ON inscriptions(height);
+CREATE INDEX idx_inscriptions_spent_height ON inscriptions(spent_height);
\ No newline at end of file
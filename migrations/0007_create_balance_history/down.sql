@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_balance_history_ticker_height;
+
+DROP TABLE balance_history;
\ No newline at end of file
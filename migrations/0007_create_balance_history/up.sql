@@ -0,0 +1,11 @@
+CREATE TABLE balance_history (
+    id SERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    ticker TEXT NOT NULL,
+    balance NUMERIC NOT NULL,
+    transfer_balance NUMERIC NOT NULL,
+    height BIGINT NOT NULL,
+    CONSTRAINT unique_address_ticker_height UNIQUE (address, ticker, height)
+);
+
+CREATE INDEX idx_balance_history_ticker_height ON balance_history(ticker, height);
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_outbox_undelivered;
+DROP INDEX IF EXISTS idx_outbox_height;
+
+DROP TABLE outbox;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE outbox (
+    id BIGSERIAL PRIMARY KEY,
+    idempotency_key TEXT UNIQUE NOT NULL,
+    height BIGINT NOT NULL,
+    payload TEXT NOT NULL,
+    delivered BOOLEAN NOT NULL DEFAULT FALSE,
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+CREATE INDEX idx_outbox_undelivered ON outbox(id) WHERE NOT delivered;
+CREATE INDEX idx_outbox_height ON outbox(height);
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP VIEW IF EXISTS balances_with_pending;
+DROP INDEX IF EXISTS idx_pending_balances_address_ticker;
+
+DROP TABLE pending_balances;
\ No newline at end of file
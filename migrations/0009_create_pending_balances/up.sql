@@ -0,0 +1,25 @@
+CREATE TABLE pending_balances (
+    id SERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    ticker TEXT NOT NULL,
+    balance NUMERIC NOT NULL DEFAULT 0,
+    transfer_balance NUMERIC NOT NULL DEFAULT 0,
+    tx_id TEXT NOT NULL
+);
+
+CREATE INDEX idx_pending_balances_address_ticker ON pending_balances(address, ticker);
+
+CREATE VIEW balances_with_pending AS
+SELECT
+    COALESCE(b.address, p.address) AS address,
+    COALESCE(b.ticker, p.ticker) AS ticker,
+    COALESCE(b.balance, 0) AS balance,
+    COALESCE(b.transfer_balance, 0) AS transfer_balance,
+    COALESCE(b.balance, 0) + COALESCE(p.balance, 0) AS pending_balance,
+    COALESCE(b.transfer_balance, 0) + COALESCE(p.transfer_balance, 0) AS pending_transfer_balance
+FROM balances b
+FULL OUTER JOIN (
+    SELECT address, ticker, SUM(balance) AS balance, SUM(transfer_balance) AS transfer_balance
+    FROM pending_balances
+    GROUP BY address, ticker
+) p ON b.address = p.address AND b.ticker = p.ticker;
\ No newline at end of file
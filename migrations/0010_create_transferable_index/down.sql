@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_inscriptions_transferable;
\ No newline at end of file
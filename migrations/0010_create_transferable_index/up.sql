@@ -0,0 +1,2 @@
+CREATE INDEX idx_inscriptions_transferable ON inscriptions(genesis_address, ticker)
+    WHERE action = 'transfer' AND valid AND NOT spent;
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE blocks (
+    height BIGINT PRIMARY KEY,
+    block_hash TEXT NOT NULL,
+    event_count INT NOT NULL DEFAULT 0,
+    event_hash TEXT NOT NULL,
+    cumulative_hash TEXT NOT NULL
+);
\ No newline at end of file
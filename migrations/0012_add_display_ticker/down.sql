@@ -0,0 +1,2 @@
+ALTER TABLE inscriptions DROP COLUMN original_ticker;
+ALTER TABLE tracker DROP COLUMN display_ticker;
\ No newline at end of file
@@ -0,0 +1,7 @@
+ALTER TABLE inscriptions ADD COLUMN original_ticker TEXT;
+UPDATE inscriptions SET original_ticker = ticker;
+ALTER TABLE inscriptions ALTER COLUMN original_ticker SET NOT NULL;
+
+ALTER TABLE tracker ADD COLUMN display_ticker TEXT;
+UPDATE tracker SET display_ticker = ticker;
+ALTER TABLE tracker ALTER COLUMN display_ticker SET NOT NULL;
\ No newline at end of file
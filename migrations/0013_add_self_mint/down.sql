@@ -0,0 +1,3 @@
+ALTER TABLE inscriptions DROP COLUMN parent_id;
+ALTER TABLE inscriptions DROP COLUMN self_mint;
+ALTER TABLE tracker DROP COLUMN self_mint;
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE inscriptions ADD COLUMN parent_id TEXT NULL;
+ALTER TABLE inscriptions ADD COLUMN self_mint BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE tracker ADD COLUMN self_mint BOOLEAN NOT NULL DEFAULT false;
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP INDEX idx_inscriptions_inscription_num;
+ALTER TABLE inscriptions ADD CONSTRAINT inscriptions_inscription_num_key UNIQUE (inscription_num);
\ No newline at end of file
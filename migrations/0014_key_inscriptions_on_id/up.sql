@@ -0,0 +1,2 @@
+ALTER TABLE inscriptions DROP CONSTRAINT inscriptions_inscription_num_key;
+CREATE INDEX idx_inscriptions_inscription_num ON inscriptions(inscription_num);
\ No newline at end of file
@@ -0,0 +1,4 @@
+UPDATE tracker SET limit_mint = 0
+FROM inscriptions
+WHERE inscriptions.inscription_id = tracker.deploy_inscription
+    AND inscriptions.limit_mint IS NULL;
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- A deploy without `lim` was stored with a zero limit, meaning unlimited; an explicit "lim":"0" keeps its own
+UPDATE tracker SET limit_mint = tracker.supply
+FROM inscriptions
+WHERE inscriptions.inscription_id = tracker.deploy_inscription
+    AND inscriptions.limit_mint IS NULL
+    AND tracker.limit_mint = 0;
\ No newline at end of file
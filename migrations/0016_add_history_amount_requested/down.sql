@@ -0,0 +1,3 @@
+UPDATE history SET amount = amount_requested WHERE partial_mint;
+ALTER TABLE history DROP COLUMN amount_requested;
+ALTER TABLE history DROP COLUMN partial_mint;
\ No newline at end of file
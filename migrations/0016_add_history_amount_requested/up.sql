@@ -0,0 +1,17 @@
+ALTER TABLE history ADD COLUMN amount_requested NUMERIC;
+UPDATE history SET amount_requested = amount;
+ALTER TABLE history ALTER COLUMN amount_requested SET NOT NULL;
+ALTER TABLE history ADD COLUMN partial_mint BOOLEAN NOT NULL DEFAULT false;
+
+-- Credit the final mint of each ticker with only the supply that remained
+WITH mints AS (
+    SELECT history.id, history.amount, tracker.supply,
+           SUM(history.amount) OVER (PARTITION BY history.ticker ORDER BY history.id) - history.amount AS minted_before
+    FROM history
+    JOIN tracker ON tracker.ticker = history.ticker
+    WHERE history.action = 'mint' AND NOT history.invalid
+)
+UPDATE history
+SET amount = mints.supply - mints.minted_before, partial_mint = true
+FROM mints
+WHERE history.id = mints.id AND mints.minted_before + mints.amount > mints.supply;
\ No newline at end of file
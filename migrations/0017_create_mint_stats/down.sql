@@ -0,0 +1,10 @@
+DROP INDEX idx_history_ticker_address_receiver;
+DROP TABLE mint_activity;
+
+ALTER TABLE tracker DROP COLUMN valid_mints;
+ALTER TABLE tracker DROP COLUMN invalid_mints;
+ALTER TABLE tracker DROP COLUMN unique_minters;
+ALTER TABLE tracker DROP COLUMN first_mint_height;
+ALTER TABLE tracker DROP COLUMN first_mint_timestamp;
+ALTER TABLE tracker DROP COLUMN last_mint_height;
+ALTER TABLE tracker DROP COLUMN last_mint_timestamp;
\ No newline at end of file
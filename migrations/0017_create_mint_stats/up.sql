@@ -0,0 +1,65 @@
+ALTER TABLE tracker ADD COLUMN valid_mints BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE tracker ADD COLUMN invalid_mints BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE tracker ADD COLUMN unique_minters BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE tracker ADD COLUMN first_mint_height BIGINT;
+ALTER TABLE tracker ADD COLUMN first_mint_timestamp BIGINT;
+ALTER TABLE tracker ADD COLUMN last_mint_height BIGINT;
+ALTER TABLE tracker ADD COLUMN last_mint_timestamp BIGINT;
+
+CREATE TABLE mint_activity (
+    id SERIAL PRIMARY KEY,
+    ticker TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    mints BIGINT NOT NULL DEFAULT 0,
+    amount NUMERIC NOT NULL DEFAULT 0,
+    UNIQUE (ticker, height)
+);
+
+CREATE INDEX idx_history_ticker_address_receiver ON history(ticker, address_receiver);
+
+-- Backfill from the mints indexed so far
+UPDATE tracker SET
+    valid_mints = mints.valid_mints,
+    unique_minters = mints.unique_minters,
+    first_mint_height = mints.first_mint_height,
+    first_mint_timestamp = mints.first_mint_timestamp,
+    last_mint_height = mints.last_mint_height,
+    last_mint_timestamp = mints.last_mint_timestamp
+FROM (
+    SELECT ticker,
+           COUNT(*) AS valid_mints,
+           COUNT(DISTINCT address_receiver) AS unique_minters,
+           MIN(height) AS first_mint_height,
+           MIN(timestamp) AS first_mint_timestamp,
+           MAX(height) AS last_mint_height,
+           MAX(timestamp) AS last_mint_timestamp
+    FROM history
+    WHERE action = 'mint' AND NOT invalid
+    GROUP BY ticker
+) mints
+WHERE tracker.ticker = mints.ticker;
+
+-- Mint inscriptions after the deploy without a history row were invalid
+UPDATE tracker SET invalid_mints = mints.invalid_mints
+FROM (
+    SELECT tracker.ticker, COUNT(*) AS invalid_mints
+    FROM inscriptions
+    JOIN tracker ON tracker.ticker = inscriptions.ticker
+    WHERE inscriptions.action = 'mint'
+        AND inscriptions.inscription_num > tracker.deploy_inscription_num
+        AND NOT EXISTS (
+            SELECT 1 FROM history
+            WHERE history.inscription_id = inscriptions.inscription_id
+                AND history.action = 'mint'
+                AND NOT history.invalid
+        )
+    GROUP BY tracker.ticker
+) mints
+WHERE tracker.ticker = mints.ticker;
+
+INSERT INTO mint_activity (ticker, height, timestamp, mints, amount)
+SELECT ticker, height, MIN(timestamp), COUNT(*), SUM(amount)
+FROM history
+WHERE action = 'mint' AND NOT invalid
+GROUP BY ticker, height;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE dead_letters (
+    id SERIAL PRIMARY KEY,
+    height BIGINT NOT NULL,
+    tx_id TEXT NOT NULL,
+    inscription_id TEXT UNIQUE NOT NULL,
+    payload TEXT NOT NULL,
+    error TEXT NOT NULL
+);
+
+CREATE INDEX idx_dead_letters_height ON dead_letters(height);
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP INDEX idx_inscriptions_output;
+ALTER TABLE inscriptions DROP COLUMN spent_output;
+ALTER TABLE inscriptions DROP COLUMN output_offset;
+ALTER TABLE inscriptions ADD CONSTRAINT inscriptions_output_key UNIQUE (output);
\ No newline at end of file
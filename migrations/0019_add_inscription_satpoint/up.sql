@@ -0,0 +1,4 @@
+ALTER TABLE inscriptions DROP CONSTRAINT inscriptions_output_key;
+ALTER TABLE inscriptions ADD COLUMN output_offset BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE inscriptions ADD COLUMN spent_output TEXT;
+CREATE INDEX idx_inscriptions_output ON inscriptions(output, output_offset);
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE inscriptions DROP COLUMN spent_tx_offset;
+ALTER TABLE inscriptions DROP COLUMN tx_offset;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE inscriptions ADD COLUMN tx_offset BIGINT;
+ALTER TABLE inscriptions ADD COLUMN spent_tx_offset BIGINT;
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP INDEX idx_inscriptions_spent_output;
+DROP INDEX idx_inscriptions_sat;
+ALTER TABLE inscriptions DROP COLUMN invalid_reason;
+ALTER TABLE inscriptions DROP COLUMN sat;
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE inscriptions ADD COLUMN sat BIGINT;
+ALTER TABLE inscriptions ADD COLUMN invalid_reason TEXT;
+CREATE INDEX idx_inscriptions_sat ON inscriptions(sat);
+CREATE INDEX idx_inscriptions_spent_output ON inscriptions(spent_output, spent_offset);
\ No newline at end of file
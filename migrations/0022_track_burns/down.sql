@@ -0,0 +1,7 @@
+UPDATE history SET action = 'send' WHERE action = 'burn';
+INSERT INTO balances (address, ticker, balance, transfer_balance)
+SELECT '', ticker, burned, 0 FROM tracker WHERE burned > 0;
+UPDATE tracker SET holders = holders + 1 WHERE burned > 0;
+
+ALTER TABLE inscriptions DROP COLUMN burned;
+ALTER TABLE tracker DROP COLUMN burned;
\ No newline at end of file
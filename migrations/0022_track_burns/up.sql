@@ -0,0 +1,17 @@
+ALTER TABLE tracker ADD COLUMN burned NUMERIC NOT NULL DEFAULT 0;
+ALTER TABLE inscriptions ADD COLUMN burned BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE inscriptions SET burned = TRUE WHERE spent AND address_receiver = '';
+
+-- Amounts sent to an output without an address were credited to the empty address
+UPDATE tracker SET burned = burns.amount
+FROM (
+    SELECT ticker, SUM(amount) AS amount FROM inscriptions
+    WHERE burned AND valid AND amount IS NOT NULL
+    GROUP BY ticker
+) burns
+WHERE tracker.ticker = burns.ticker;
+UPDATE tracker SET holders = holders - 1
+WHERE ticker IN (SELECT ticker FROM balances WHERE address = '' AND balance + transfer_balance > 0);
+DELETE FROM balances WHERE address = '';
+DELETE FROM balance_history WHERE address = '';
+UPDATE history SET action = 'burn' WHERE action = 'send' AND address_receiver = '';
\ No newline at end of file
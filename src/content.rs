@@ -0,0 +1,334 @@
+use crate::models::Ord20;
+use crate::ordinals::{ContentFetchError, Ordinals};
+use brotli::Decompressor;
+use flate2::read::GzDecoder;
+use hex::decode;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::time::Duration;
+
+/// Decoded inscription bodies larger than this are rejected, so a small compressed body cannot
+/// expand without bound.
+const MAX_DECODED_SIZE: u64 = 1024 * 1024;
+
+/// How many times a body the ordinals server could not serve is fetched again, waiting 1, 2, 4...
+/// seconds in between, before the inscription is dead-lettered.
+const CONTENT_FETCH_RETRIES: u32 = 3;
+
+/// A parsed `type/subtype; name=value` media type. The essence and parameter names are
+/// lowercased; parameter values keep their case with surrounding quotes removed.
+#[derive(Debug, PartialEq)]
+pub struct MediaType {
+    pub essence: String,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl MediaType {
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the body is UTF-8 text: no charset, or a UTF-8 compatible one.
+    pub fn is_utf8(&self) -> bool {
+        self.parameter("charset").is_none_or(|charset| {
+            ["utf-8", "utf8", "us-ascii", "ascii"].contains(&charset.to_lowercase().as_str())
+        })
+    }
+}
+
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// Parses a content type per RFC 2045. Returns `None` when the type or subtype is missing or
+/// not a valid token. Malformed parameters are ignored.
+pub fn parse_media_type(content_type: &str) -> Option<MediaType> {
+    let mut parts = content_type.split(';');
+    let essence = parts.next()?.trim().to_lowercase();
+    let (kind, subtype) = essence.split_once('/')?;
+    if !is_token(kind) || !is_token(subtype) {
+        return None;
+    }
+
+    let parameters = parts
+        .filter_map(|parameter| {
+            let (name, value) = parameter.split_once('=')?;
+            let name = name.trim().to_lowercase();
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            is_token(&name).then(|| (name, value.to_string()))
+        })
+        .collect();
+
+    Some(MediaType {
+        essence,
+        parameters,
+    })
+}
+
+/// Undoes the `content-encoding` of an inscription body. `br` and `gzip` bodies are
+/// decompressed; a missing or `identity` encoding returns the body unchanged.
+pub fn decode_body(body: &[u8], content_encoding: Option<&str>) -> Result<Vec<u8>, String> {
+    let encoding = content_encoding
+        .map(|encoding| encoding.trim().to_lowercase())
+        .unwrap_or_default();
+    let reader: Box<dyn Read + '_> = match encoding.as_str() {
+        "" | "identity" => return Ok(body.to_vec()),
+        "br" => Box::new(Decompressor::new(body, 4096)),
+        "gzip" => Box::new(GzDecoder::new(body)),
+        encoding => return Err(format!("Unsupported content encoding '{}'", encoding)),
+    };
+
+    let mut decoded = Vec::new();
+    reader
+        .take(MAX_DECODED_SIZE + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|err| format!("Failed to decode {} body: {}", encoding, err))?;
+    if decoded.len() as u64 > MAX_DECODED_SIZE {
+        return Err(format!("Decoded body exceeds {} bytes", MAX_DECODED_SIZE));
+    }
+    Ok(decoded)
+}
+
+/// Decodes the hex encoded body and content encoding an ordinals server reports for an
+/// inscription, then undoes the content encoding.
+pub fn inscription_body(
+    content: Option<&str>,
+    content_encoding: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let content = content.ok_or("Missing content")?;
+    let body = decode(content).map_err(|err| format!("Content is not hex: {}", err))?;
+    let content_encoding = content_encoding
+        .map(|encoding| {
+            decode(encoding)
+                .map(|encoding| String::from_utf8_lossy(&encoding).into_owned())
+                .map_err(|err| format!("Content encoding is not hex: {}", err))
+        })
+        .transpose()?;
+    decode_body(&body, content_encoding.as_deref())
+}
+
+/// Parses an inscription body as an operation of `meta_protocol`. Bodies that are not JSON
+/// objects or belong to another protocol are `Ok(None)`; a body that names the protocol but
+/// is not a well-formed operation is an error.
+pub fn parse_operation(content: &str, meta_protocol: &str) -> Result<Option<Ord20>, String> {
+    let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(content) else {
+        return Ok(None);
+    };
+    if !fields
+        .get("p")
+        .and_then(Value::as_str)
+        .is_some_and(|p| p.contains(meta_protocol))
+    {
+        return Ok(None);
+    }
+    serde_json::from_value::<Ord20>(Value::Object(fields))
+        .map(Some)
+        .map_err(|err| format!("Malformed {} operation: {}", meta_protocol, err))
+}
+
+/// An inscription body as the ordinals server serves it, and its `content-encoding`.
+pub type FetchedContent = (Vec<u8>, Option<String>);
+
+/// Inscription bodies fetched from the ordinals server, keyed by inscription id. Holds at most
+/// `capacity` bodies and evicts the oldest first.
+pub struct ContentCache {
+    pub capacity: usize,
+    pub max_size: usize,
+    bodies: HashMap<String, FetchedContent>,
+    order: VecDeque<String>,
+}
+
+impl ContentCache {
+    pub fn new(capacity: usize, max_size: usize) -> ContentCache {
+        ContentCache {
+            capacity,
+            max_size,
+            bodies: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&self, inscription_id: &str) -> Option<&FetchedContent> {
+        self.bodies.get(inscription_id)
+    }
+
+    pub fn insert(&mut self, inscription_id: String, body: FetchedContent) {
+        if self.capacity == 0 || self.bodies.contains_key(&inscription_id) {
+            return;
+        }
+        while self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.bodies.remove(&oldest);
+            }
+        }
+        self.order.push_back(inscription_id.clone());
+        self.bodies.insert(inscription_id, body);
+    }
+}
+
+/// Fetches the body of an inscription whose content the block data left out from the
+/// ordinals server's `/content/{id}` endpoint, with its content encoding. Fails with
+/// `Unavailable` when the server still cannot serve it after the retries.
+pub async fn fetch_content(
+    ordinals: &mut Ordinals,
+    cache: &mut ContentCache,
+    inscription_id: &str,
+) -> Result<FetchedContent, ContentFetchError> {
+    if let Some(body) = cache.get(inscription_id) {
+        return Ok(body.clone());
+    }
+    let mut attempt = 0;
+    let fetched = loop {
+        match ordinals
+            .get_inscription_content(inscription_id.to_string(), cache.max_size)
+            .await
+        {
+            Ok(fetched) => break fetched,
+            Err(ContentFetchError::Unavailable(err)) if attempt < CONTENT_FETCH_RETRIES => {
+                log::warn!(
+                    "Failed to fetch content of {}, retrying: {}",
+                    inscription_id,
+                    err
+                );
+                tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    };
+    cache.insert(inscription_id.to_string(), fetched.clone());
+    Ok(fetched)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::{
+        decode_body, inscription_body, parse_media_type, parse_operation, ContentCache,
+    };
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_media_type() {
+        let media_type = parse_media_type(" Text/Plain ; Charset=\"UTF-8\"").unwrap();
+        assert_eq!(media_type.essence, "text/plain");
+        assert_eq!(media_type.parameter("charset"), Some("UTF-8"));
+        assert!(media_type.is_utf8());
+
+        assert!(parse_media_type("application/json").unwrap().is_utf8());
+        assert!(!parse_media_type("text/plain;charset=utf-16")
+            .unwrap()
+            .is_utf8());
+        assert_eq!(
+            parse_media_type("text/markdown;variant=GFM;charset").map(|m| m.parameters),
+            Some(vec![("variant".to_string(), "GFM".to_string())])
+        );
+        assert_eq!(parse_media_type("text"), None);
+        assert_eq!(parse_media_type("text/"), None);
+        assert_eq!(parse_media_type("text/plain x"), None);
+    }
+
+    #[test]
+    fn test_decode_body() {
+        let body = br#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#;
+
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(body).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        let mut br = Vec::new();
+        brotli::CompressorWriter::new(&mut br, 4096, 11, 22)
+            .write_all(body)
+            .unwrap();
+
+        assert_eq!(decode_body(body, None).unwrap(), body);
+        assert_eq!(decode_body(body, Some("identity")).unwrap(), body);
+        assert_eq!(decode_body(&gzip, Some("gzip")).unwrap(), body);
+        assert_eq!(decode_body(&br, Some("BR")).unwrap(), body);
+        assert!(decode_body(body, Some("gzip")).is_err());
+        assert!(decode_body(body, Some("zstd")).is_err());
+
+        let mut bomb = GzEncoder::new(Vec::new(), Compression::best());
+        bomb.write_all(&vec![b' '; 2 * 1024 * 1024]).unwrap();
+        assert!(decode_body(&bomb.finish().unwrap(), Some("gzip")).is_err());
+    }
+
+    #[test]
+    fn test_malformed_inscriptions() {
+        let body = r#"{"p":"ltc-20","op":"mint","tick":"lite","amt":"10"}"#;
+        assert_eq!(
+            inscription_body(Some(&hex::encode(body)), None).unwrap(),
+            body.as_bytes()
+        );
+        assert!(inscription_body(None, None).is_err());
+        assert!(inscription_body(Some("not hex"), None).is_err());
+        assert!(inscription_body(Some(&hex::encode(body)), Some("zz")).is_err());
+        assert!(inscription_body(Some(&hex::encode(body)), Some(&hex::encode("br"))).is_err());
+
+        assert_eq!(
+            parse_operation(body, "ltc-20")
+                .unwrap()
+                .map(|ord20| ord20.tick),
+            Some("lite".to_string())
+        );
+        assert!(parse_operation("hello", "ltc-20").unwrap().is_none());
+        assert!(parse_operation("[1,2]", "ltc-20").unwrap().is_none());
+        assert!(parse_operation(body, "brc-20").unwrap().is_none());
+        assert!(parse_operation(r#"{"p":"ltc-20","op":"mint"}"#, "ltc-20").is_err());
+        assert!(parse_operation(
+            r#"{"p":"ltc-20","op":"deploy","tick":"lite","max":"1000","dec":"x"}"#,
+            "ltc-20"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_deploy_decimals() {
+        let dec = |deploy: &str| parse_operation(deploy, "ltc-20").map(|ord20| ord20.unwrap().dec);
+        assert_eq!(
+            dec(r#"{"p":"ltc-20","op":"deploy","tick":"lite","max":"1000","dec":"8"}"#),
+            Ok(Some(8))
+        );
+        assert_eq!(
+            dec(r#"{"p":"ltc-20","op":"deploy","tick":"lite","max":"1000","dec":8}"#),
+            Ok(Some(8))
+        );
+        assert_eq!(
+            dec(r#"{"p":"ltc-20","op":"deploy","tick":"lite","max":"1000"}"#),
+            Ok(None)
+        );
+        assert!(
+            dec(r#"{"p":"ltc-20","op":"deploy","tick":"lite","max":"1000","dec":"8.5"}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_content_cache_eviction() {
+        let mut cache = ContentCache::new(2, 1024);
+        cache.insert("ai0".to_string(), (b"a".to_vec(), None));
+        cache.insert("bi0".to_string(), (b"b".to_vec(), None));
+        cache.insert("ai0".to_string(), (b"x".to_vec(), None));
+        assert_eq!(cache.get("ai0"), Some(&(b"a".to_vec(), None)));
+
+        cache.insert("ci0".to_string(), (b"c".to_vec(), None));
+        assert_eq!(cache.get("ai0"), None);
+        assert_eq!(cache.get("bi0"), Some(&(b"b".to_vec(), None)));
+        assert_eq!(cache.get("ci0"), Some(&(b"c".to_vec(), None)));
+
+        let mut disabled = ContentCache::new(0, 1024);
+        disabled.insert("ai0".to_string(), (b"a".to_vec(), None));
+        assert_eq!(disabled.get("ai0"), None);
+    }
+}
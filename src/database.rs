@@ -2,14 +2,22 @@ extern crate diesel;
 extern crate dotenv;
 
 use crate::models::{
-    Balances, BalancesInsert, History, HistoryInsert, Inscriptions, InscriptionsInsert, Status,
-    Tracker, TrackerInsert,
+    BalanceHistory, BalanceHistoryInsert, Balances, BalancesInsert, Blocks, BlocksInsert,
+    DeadLetters, DeadLettersInsert, History, HistoryInsert, Inscriptions, InscriptionsInsert,
+    MintActivity, MintActivityInsert, Outbox, OutboxInsert, PendingBalancesInsert, Status,
+    StatusInsert, Tracker, TrackerInsert, TrackerMintStats,
 };
-use crate::schema::{balances, history, inscriptions, status, tracker};
-use bigdecimal::BigDecimal;
+use crate::replay::InscriptionOrderKey;
+use crate::schema::{
+    balance_history, balances, blocks, dead_letters, history, inscriptions, mint_activity, outbox,
+    pending_balances, status, tracker,
+};
+use bigdecimal::{BigDecimal, Zero};
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use diesel::result::Error;
+use diesel::sql_types::Text;
 use dotenv::dotenv;
 use std::env;
 
@@ -31,6 +39,37 @@ impl Database {
         Ok(Database { connection })
     }
 
+    pub fn begin_transaction(&mut self) -> QueryResult<()> {
+        AnsiTransactionManager::begin_transaction(&mut self.connection)
+    }
+
+    pub fn commit_transaction(&mut self) -> QueryResult<()> {
+        AnsiTransactionManager::commit_transaction(&mut self.connection)
+    }
+
+    pub fn rollback_transaction(&mut self) -> QueryResult<()> {
+        AnsiTransactionManager::rollback_transaction(&mut self.connection)
+    }
+
+    /// Clears all derived token state so it can be rebuilt from the `inscriptions` table.
+    pub fn reset_state(&mut self) -> QueryResult<()> {
+        diesel::sql_query(
+            "TRUNCATE balances, balance_history, tracker, history, mint_activity RESTART IDENTITY",
+        )
+        .execute(&mut self.connection)?;
+        diesel::update(inscriptions::table)
+            .set(inscriptions::valid.eq(false))
+            .execute(&mut self.connection)?;
+        Ok(())
+    }
+
+    pub fn notify(&mut self, channel: &str, payload: &str) -> QueryResult<usize> {
+        diesel::sql_query("SELECT pg_notify($1, $2)")
+            .bind::<Text, _>(channel)
+            .bind::<Text, _>(payload)
+            .execute(&mut self.connection)
+    }
+
     pub fn get_status(&mut self, key: String) -> QueryResult<Status> {
         status::table
             .filter(status::key.eq(&key))
@@ -38,8 +77,13 @@ impl Database {
     }
 
     pub fn update_status(&mut self, key: String, value: String) -> QueryResult<Status> {
-        diesel::update(status::table)
-            .filter(status::key.eq(&key))
+        diesel::insert_into(status::table)
+            .values(&StatusInsert {
+                key,
+                value: Some(value.clone()),
+            })
+            .on_conflict(status::key)
+            .do_update()
             .set(status::value.eq(&value))
             .get_result(&mut self.connection)
     }
@@ -53,19 +97,28 @@ impl Database {
         inscription_num: i64,
         height: i64,
         output: String,
+        output_offset: i64,
         timestamp: i64,
         ticker: String,
+        original_ticker: String,
         action: String,
         supply: Option<BigDecimal>,
         limit_mint: Option<BigDecimal>,
         decimal: Option<i32>,
         amount: Option<BigDecimal>,
         value: i64,
+        tx_index: i64,
+        tx_offset: i64,
+        parent_id: Option<String>,
+        self_mint: bool,
+        sat: Option<i64>,
+        invalid_reason: Option<String>,
     ) -> QueryResult<Inscriptions> {
         let new_inscription = InscriptionsInsert {
             genesis_tx_id: tx_id,
             genesis_address: address,
             ticker,
+            original_ticker,
             action,
             supply,
             limit_mint,
@@ -77,6 +130,13 @@ impl Database {
             output,
             height,
             timestamp,
+            tx_index,
+            parent_id,
+            self_mint,
+            output_offset,
+            tx_offset,
+            sat,
+            invalid_reason,
         };
 
         diesel::insert_into(inscriptions::table)
@@ -85,17 +145,50 @@ impl Database {
             .get_result(&mut self.connection)
     }
 
-    #[allow(unused)]
     pub fn get_inscription(&mut self, id: i32) -> QueryResult<Inscriptions> {
         inscriptions::table
             .filter(inscriptions::id.eq(&id))
             .first(&mut self.connection)
     }
 
-    pub fn get_inscription_by_output(&mut self, output: String) -> QueryResult<Inscriptions> {
+    /// Returns the inscriptions created on `output`, in the order of their sats in it.
+    pub fn get_inscription_by_output(&mut self, output: String) -> QueryResult<Vec<Inscriptions>> {
         inscriptions::table
             .filter(inscriptions::output.eq(&output))
-            .first(&mut self.connection)
+            .order((
+                inscriptions::output_offset.asc(),
+                inscriptions::inscription_num.asc(),
+            ))
+            .load(&mut self.connection)
+    }
+
+    /// Returns the inscriptions other than `inscription_id` created on `sat`, or created on or
+    /// sent to `output`.
+    pub fn get_inscriptions_near_sat(
+        &mut self,
+        inscription_id: String,
+        sat: Option<i64>,
+        output: Option<String>,
+    ) -> QueryResult<Vec<Inscriptions>> {
+        let mut query = inscriptions::table
+            .filter(inscriptions::inscription_id.ne(inscription_id))
+            .into_boxed();
+        if let Some(output) = output {
+            let on_output = inscriptions::output
+                .eq(output.clone())
+                .or(inscriptions::spent_output.eq(output));
+            query = match sat {
+                Some(sat) => query.filter(on_output.or(inscriptions::sat.eq(sat))),
+                None => query.filter(on_output),
+            };
+        } else if let Some(sat) = sat {
+            query = query.filter(inscriptions::sat.eq(sat));
+        } else {
+            return Ok(Vec::new());
+        }
+        query
+            .select(Inscriptions::as_select())
+            .load(&mut self.connection)
     }
 
     pub fn update_inscription_valid(&mut self, id: i32, valid: bool) -> QueryResult<Inscriptions> {
@@ -105,6 +198,27 @@ impl Database {
             .get_result(&mut self.connection)
     }
 
+    /// Sets each inscription's `ticker` to the `key` of its `original_ticker` where they differ.
+    pub fn rekey_inscription_tickers(
+        &mut self,
+        key: impl Fn(&str) -> String,
+    ) -> QueryResult<usize> {
+        let original_tickers = inscriptions::table
+            .select(inscriptions::original_ticker)
+            .distinct()
+            .load::<String>(&mut self.connection)?;
+        let mut updated = 0;
+        for original_ticker in original_tickers {
+            let ticker = key(&original_ticker);
+            updated += diesel::update(inscriptions::table)
+                .filter(inscriptions::original_ticker.eq(&original_ticker))
+                .filter(inscriptions::ticker.ne(&ticker))
+                .set(inscriptions::ticker.eq(&ticker))
+                .execute(&mut self.connection)?;
+        }
+        Ok(updated)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn update_inscription_spent(
         &mut self,
@@ -112,9 +226,13 @@ impl Database {
         address_sender: String,
         address_receiver: String,
         spent_tx: String,
+        spent_output: Option<String>,
         spent_offset: i64,
         spent_height: i64,
         spent_timestamp: i64,
+        spent_tx_index: i64,
+        spent_tx_offset: i64,
+        burned: bool,
     ) -> QueryResult<Inscriptions> {
         diesel::update(inscriptions::table)
             .filter(inscriptions::id.eq(&id))
@@ -123,13 +241,97 @@ impl Database {
                 inscriptions::address_receiver.eq(&address_receiver),
                 inscriptions::spent_tx.eq(&spent_tx),
                 inscriptions::spent.eq(true),
+                inscriptions::spent_output.eq(&spent_output),
                 inscriptions::spent_offset.eq(&spent_offset),
                 inscriptions::spent_height.eq(&spent_height),
                 inscriptions::spent_timestamp.eq(&spent_timestamp),
+                inscriptions::spent_tx_index.eq(&spent_tx_index),
+                inscriptions::spent_tx_offset.eq(&spent_tx_offset),
+                inscriptions::burned.eq(&burned),
             ))
             .get_result(&mut self.connection)
     }
 
+    pub fn get_unspent_transfer_inscriptions(
+        &mut self,
+        ticker: String,
+    ) -> QueryResult<Vec<Inscriptions>> {
+        inscriptions::table
+            .filter(
+                inscriptions::ticker
+                    .eq(&ticker)
+                    .and(inscriptions::action.eq("transfer"))
+                    .and(inscriptions::valid.eq(true))
+                    .and(inscriptions::spent.eq(false)),
+            )
+            .order(inscriptions::id.asc())
+            .load(&mut self.connection)
+    }
+
+    /// Returns the valid transfer inscriptions owned by `address` that have not been sent yet,
+    /// optionally limited to one `ticker`, oldest first.
+    pub fn get_transferable_inscriptions(
+        &mut self,
+        address: String,
+        ticker: Option<String>,
+    ) -> QueryResult<Vec<Inscriptions>> {
+        let mut query = inscriptions::table
+            .filter(
+                inscriptions::genesis_address
+                    .eq(address)
+                    .and(inscriptions::action.eq("transfer"))
+                    .and(inscriptions::valid.eq(true))
+                    .and(inscriptions::spent.eq(false)),
+            )
+            .into_boxed();
+        if let Some(ticker) = ticker {
+            query = query.filter(inscriptions::ticker.eq(ticker));
+        }
+        query
+            .order(inscriptions::inscription_num.asc())
+            .load(&mut self.connection)
+    }
+
+    pub fn delete_inscriptions_from_height(&mut self, height: i64) -> QueryResult<usize> {
+        diesel::delete(inscriptions::table.filter(inscriptions::height.ge(&height)))
+            .execute(&mut self.connection)
+    }
+
+    pub fn unspend_inscriptions_from_height(&mut self, height: i64) -> QueryResult<usize> {
+        diesel::update(inscriptions::table)
+            .filter(inscriptions::spent_height.ge(&height))
+            .set((
+                inscriptions::address_sender.eq(None::<String>),
+                inscriptions::address_receiver.eq(None::<String>),
+                inscriptions::spent.eq(false),
+                inscriptions::spent_tx.eq(None::<String>),
+                inscriptions::spent_output.eq(None::<String>),
+                inscriptions::spent_offset.eq(None::<i64>),
+                inscriptions::spent_height.eq(None::<i64>),
+                inscriptions::spent_timestamp.eq(None::<i64>),
+                inscriptions::spent_tx_index.eq(None::<i64>),
+                inscriptions::spent_tx_offset.eq(None::<i64>),
+                inscriptions::burned.eq(false),
+            ))
+            .execute(&mut self.connection)
+    }
+
+    pub fn get_inscription_order_keys(&mut self) -> QueryResult<Vec<InscriptionOrderKey>> {
+        inscriptions::table
+            .select((
+                inscriptions::id,
+                inscriptions::height,
+                inscriptions::tx_index,
+                inscriptions::tx_offset,
+                inscriptions::inscription_num,
+                inscriptions::spent_height,
+                inscriptions::spent_tx_index,
+                inscriptions::spent_tx_offset,
+            ))
+            .order(inscriptions::id.asc())
+            .load(&mut self.connection)
+    }
+
     #[allow(unused)]
     pub fn get_latest_inscription(&mut self) -> QueryResult<Inscriptions> {
         inscriptions::table
@@ -137,29 +339,33 @@ impl Database {
             .first(&mut self.connection)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_tracker(
         &mut self,
         ticker: String,
+        display_ticker: String,
         inscription_id: String,
         deploy_inscription_num: i64,
         supply: BigDecimal,
         limit: BigDecimal,
         decimals: i32,
-    ) -> Tracker {
+        self_mint: bool,
+    ) -> QueryResult<Tracker> {
         let new_tracker = TrackerInsert {
             deploy_inscription_num,
             deploy_inscription: inscription_id,
             ticker,
+            display_ticker,
             supply,
             limit_mint: limit,
             decimals,
+            self_mint,
         };
 
         diesel::insert_into(tracker::table)
             .values(&new_tracker)
             .returning(Tracker::as_returning())
             .get_result(&mut self.connection)
-            .expect("Error saving new tracker")
     }
 
     pub fn get_tracker(&mut self, ticker: String) -> QueryResult<Tracker> {
@@ -168,6 +374,12 @@ impl Database {
             .first(&mut self.connection)
     }
 
+    pub fn get_trackers(&mut self) -> QueryResult<Vec<Tracker>> {
+        tracker::table
+            .order(tracker::id.asc())
+            .load(&mut self.connection)
+    }
+
     pub fn update_tracker_minted(
         &mut self,
         id: i32,
@@ -197,6 +409,13 @@ impl Database {
             .get_result(&mut self.connection)
     }
 
+    pub fn update_tracker_burned(&mut self, id: i32, burned: BigDecimal) -> QueryResult<Tracker> {
+        diesel::update(tracker::table)
+            .filter(tracker::id.eq(&id))
+            .set(tracker::burned.eq(&burned))
+            .get_result(&mut self.connection)
+    }
+
     pub fn update_tracker_mint_start(&mut self, id: i32, mint_start: i64) -> QueryResult<Tracker> {
         diesel::update(tracker::table)
             .filter(tracker::id.eq(&id))
@@ -211,6 +430,28 @@ impl Database {
             .get_result(&mut self.connection)
     }
 
+    pub fn update_tracker_mint_stats(
+        &mut self,
+        id: i32,
+        stats: &TrackerMintStats,
+    ) -> QueryResult<Tracker> {
+        diesel::update(tracker::table)
+            .filter(tracker::id.eq(&id))
+            .set(stats)
+            .get_result(&mut self.connection)
+    }
+
+    pub fn update_tracker_invalid_mints(
+        &mut self,
+        id: i32,
+        invalid_mints: i64,
+    ) -> QueryResult<Tracker> {
+        diesel::update(tracker::table)
+            .filter(tracker::id.eq(&id))
+            .set(tracker::invalid_mints.eq(&invalid_mints))
+            .get_result(&mut self.connection)
+    }
+
     pub fn create_balance(&mut self, address: String, ticker: String) -> QueryResult<Balances> {
         let new_balance = BalancesInsert { address, ticker };
 
@@ -230,13 +471,42 @@ impl Database {
             .first(&mut self.connection)
     }
 
+    pub fn get_balances_by_ticker(&mut self, ticker: String) -> QueryResult<Vec<Balances>> {
+        balances::table
+            .filter(balances::ticker.eq(&ticker))
+            .order(balances::id.asc())
+            .load(&mut self.connection)
+    }
+
     pub fn update_balance(
         &mut self,
         address: String,
         ticker: String,
         balance: BigDecimal,
         transfer_balance: BigDecimal,
+        height: i64,
     ) -> QueryResult<Balances> {
+        let balance_history = BalanceHistoryInsert {
+            address: address.clone(),
+            ticker: ticker.clone(),
+            balance: balance.clone(),
+            transfer_balance: transfer_balance.clone(),
+            height,
+        };
+        diesel::insert_into(balance_history::table)
+            .values(&balance_history)
+            .on_conflict((
+                balance_history::address,
+                balance_history::ticker,
+                balance_history::height,
+            ))
+            .do_update()
+            .set((
+                balance_history::balance.eq(&balance),
+                balance_history::transfer_balance.eq(&transfer_balance),
+            ))
+            .execute(&mut self.connection)?;
+
         diesel::update(balances::table)
             .filter(
                 balances::address
@@ -250,15 +520,69 @@ impl Database {
             .get_result(&mut self.connection)
     }
 
+    /// Returns the balance of `address` for `ticker` as it was after block `height` was indexed.
+    pub fn get_balance_at_height(
+        &mut self,
+        address: String,
+        ticker: String,
+        height: i64,
+    ) -> QueryResult<BalanceHistory> {
+        balance_history::table
+            .filter(
+                balance_history::address
+                    .eq(&address)
+                    .and(balance_history::ticker.eq(&ticker))
+                    .and(balance_history::height.le(&height)),
+            )
+            .order(balance_history::height.desc())
+            .first(&mut self.connection)
+    }
+
+    /// Returns the balance of every address that has held `ticker` as it was after block
+    /// `height` was indexed.
+    pub fn get_balances_at_height(
+        &mut self,
+        ticker: String,
+        height: i64,
+    ) -> QueryResult<Vec<BalanceHistory>> {
+        balance_history::table
+            .filter(
+                balance_history::ticker
+                    .eq(&ticker)
+                    .and(balance_history::height.le(&height)),
+            )
+            .distinct_on(balance_history::address)
+            .order((balance_history::address, balance_history::height.desc()))
+            .load(&mut self.connection)
+    }
+
+    /// Returns the holders of `ticker` with a non-zero balance after block `height` was indexed.
+    pub fn get_holders_at_height(
+        &mut self,
+        ticker: String,
+        height: i64,
+    ) -> QueryResult<Vec<BalanceHistory>> {
+        Ok(self
+            .get_balances_at_height(ticker, height)?
+            .into_iter()
+            .filter(|balance| {
+                balance.balance != BigDecimal::zero()
+                    || balance.transfer_balance != BigDecimal::zero()
+            })
+            .collect())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn create_history(
         &mut self,
         address_sender: String,
         address_receiver: String,
         amount: BigDecimal,
+        amount_requested: BigDecimal,
         ticker: String,
         action: String,
         invalid: bool,
+        partial_mint: bool,
         inscription_id: String,
         inscription_num: i64,
         tx_id: String,
@@ -269,9 +593,11 @@ impl Database {
             address_sender: Some(address_sender),
             address_receiver: Some(address_receiver),
             amount,
+            amount_requested,
             ticker,
             action,
             invalid,
+            partial_mint,
             inscription_id,
             inscription_num,
             tx_id,
@@ -284,4 +610,213 @@ impl Database {
             .returning(History::as_returning())
             .get_result(&mut self.connection)
     }
+
+    pub fn has_minted(&mut self, address: String, ticker: String) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            history::table
+                .filter(history::ticker.eq(&ticker))
+                .filter(history::address_receiver.eq(&address))
+                .filter(history::action.eq("mint"))
+                .filter(history::invalid.eq(false)),
+        ))
+        .get_result(&mut self.connection)
+    }
+
+    /// Adds one mint of `amount` to the ticker's activity at `height`.
+    pub fn add_mint_activity(
+        &mut self,
+        ticker: String,
+        height: i64,
+        timestamp: i64,
+        amount: BigDecimal,
+    ) -> QueryResult<usize> {
+        let activity = MintActivityInsert {
+            ticker,
+            height,
+            timestamp,
+            mints: 1,
+            amount: amount.clone(),
+        };
+
+        diesel::insert_into(mint_activity::table)
+            .values(&activity)
+            .on_conflict((mint_activity::ticker, mint_activity::height))
+            .do_update()
+            .set((
+                mint_activity::mints.eq(mint_activity::mints + 1),
+                mint_activity::amount.eq(mint_activity::amount + amount),
+            ))
+            .execute(&mut self.connection)
+    }
+
+    pub fn get_mint_activity(&mut self, ticker: String) -> QueryResult<Vec<MintActivity>> {
+        mint_activity::table
+            .filter(mint_activity::ticker.eq(&ticker))
+            .order(mint_activity::height.asc())
+            .load(&mut self.connection)
+    }
+
+    pub fn get_history_by_address(
+        &mut self,
+        address: String,
+        ticker: String,
+    ) -> QueryResult<Vec<History>> {
+        history::table
+            .filter(
+                history::ticker.eq(&ticker).and(
+                    history::address_sender
+                        .eq(&address)
+                        .or(history::address_receiver.eq(&address)),
+                ),
+            )
+            .order(history::id.asc())
+            .select(History::as_select())
+            .load(&mut self.connection)
+    }
+
+    pub fn get_history_by_ticker(&mut self, ticker: String) -> QueryResult<Vec<History>> {
+        history::table
+            .filter(history::ticker.eq(&ticker))
+            .order(history::id.asc())
+            .select(History::as_select())
+            .load(&mut self.connection)
+    }
+
+    pub fn create_outbox(&mut self, entries: &[OutboxInsert]) -> QueryResult<usize> {
+        diesel::insert_into(outbox::table)
+            .values(entries)
+            .on_conflict(outbox::idempotency_key)
+            .do_nothing()
+            .execute(&mut self.connection)
+    }
+
+    pub fn get_undelivered_outbox(&mut self, limit: i64) -> QueryResult<Vec<Outbox>> {
+        outbox::table
+            .filter(outbox::delivered.eq(false))
+            .order(outbox::id.asc())
+            .limit(limit)
+            .load(&mut self.connection)
+    }
+
+    pub fn update_outbox_delivered(&mut self, ids: &[i64]) -> QueryResult<usize> {
+        diesel::update(outbox::table)
+            .filter(outbox::id.eq_any(ids))
+            .set(outbox::delivered.eq(true))
+            .execute(&mut self.connection)
+    }
+
+    pub fn update_outbox_failed(&mut self, ids: &[i64], error: String) -> QueryResult<usize> {
+        diesel::update(outbox::table)
+            .filter(outbox::id.eq_any(ids))
+            .set((
+                outbox::attempts.eq(outbox::attempts + 1),
+                outbox::last_error.eq(&error),
+            ))
+            .execute(&mut self.connection)
+    }
+
+    pub fn delete_outbox_from_height(&mut self, height: i64) -> QueryResult<usize> {
+        diesel::delete(outbox::table.filter(outbox::height.ge(&height)))
+            .execute(&mut self.connection)
+    }
+
+    /// Replaces the provisional balance layer with `changes` in a single transaction.
+    pub fn replace_pending_balances(
+        &mut self,
+        changes: &[PendingBalancesInsert],
+    ) -> QueryResult<usize> {
+        self.connection.transaction(|connection| {
+            diesel::delete(pending_balances::table).execute(connection)?;
+            diesel::insert_into(pending_balances::table)
+                .values(changes)
+                .execute(connection)
+        })
+    }
+
+    pub fn create_block(
+        &mut self,
+        height: i64,
+        block_hash: String,
+        event_count: i32,
+        event_hash: String,
+        cumulative_hash: String,
+    ) -> QueryResult<Blocks> {
+        let new_block = BlocksInsert {
+            height,
+            block_hash,
+            event_count,
+            event_hash,
+            cumulative_hash,
+        };
+
+        diesel::insert_into(blocks::table)
+            .values(&new_block)
+            .on_conflict(blocks::height)
+            .do_update()
+            .set((
+                blocks::block_hash.eq(&new_block.block_hash),
+                blocks::event_count.eq(&new_block.event_count),
+                blocks::event_hash.eq(&new_block.event_hash),
+                blocks::cumulative_hash.eq(&new_block.cumulative_hash),
+            ))
+            .returning(Blocks::as_returning())
+            .get_result(&mut self.connection)
+    }
+
+    pub fn get_block(&mut self, height: i64) -> QueryResult<Blocks> {
+        blocks::table
+            .filter(blocks::height.eq(&height))
+            .first(&mut self.connection)
+    }
+
+    pub fn has_blocks_before(&mut self, height: i64) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            blocks::table.filter(blocks::height.lt(&height)),
+        ))
+        .get_result(&mut self.connection)
+    }
+
+    pub fn get_blocks(&mut self) -> QueryResult<Vec<Blocks>> {
+        blocks::table
+            .order(blocks::height.asc())
+            .load(&mut self.connection)
+    }
+
+    pub fn delete_blocks_from_height(&mut self, height: i64) -> QueryResult<usize> {
+        diesel::delete(blocks::table.filter(blocks::height.ge(&height)))
+            .execute(&mut self.connection)
+    }
+
+    pub fn create_dead_letter(
+        &mut self,
+        height: i64,
+        tx_id: String,
+        inscription_id: String,
+        payload: String,
+        error: String,
+    ) -> QueryResult<usize> {
+        let dead_letter = DeadLettersInsert {
+            height,
+            tx_id,
+            inscription_id,
+            payload,
+            error,
+        };
+
+        diesel::insert_into(dead_letters::table)
+            .values(&dead_letter)
+            .execute(&mut self.connection)
+    }
+
+    pub fn get_dead_letters(&mut self, from_height: i64) -> QueryResult<Vec<DeadLetters>> {
+        dead_letters::table
+            .filter(dead_letters::height.ge(&from_height))
+            .order(dead_letters::id.asc())
+            .load(&mut self.connection)
+    }
+
+    pub fn delete_dead_letters_from_height(&mut self, height: i64) -> QueryResult<usize> {
+        diesel::delete(dead_letters::table.filter(dead_letters::height.ge(&height)))
+            .execute(&mut self.connection)
+    }
 }
@@ -0,0 +1,435 @@
+use crate::indexer::Indexer;
+use crate::models::{Balances, Tracker};
+use crate::util::canonical_ticker;
+use crate::verify::Discrepancy;
+use bigdecimal::{BigDecimal, Zero};
+use diesel::OptionalExtension;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+/// A row of a reference dump, with optional fields left as `None` when the dump lacks them.
+#[derive(Debug, Default, PartialEq)]
+pub struct ReferenceRow {
+    pub ticker: String,
+    pub address: Option<String>,
+    pub balance: Option<BigDecimal>,
+    pub transfer_balance: Option<BigDecimal>,
+    pub total: Option<BigDecimal>,
+    pub supply: Option<BigDecimal>,
+    pub supply_minted: Option<BigDecimal>,
+    pub holders: Option<i64>,
+}
+
+const BALANCE_FIELDS: &[&str] = &["balance", "available_balance"];
+const TRANSFER_BALANCE_FIELDS: &[&str] = &["transfer_balance", "transferable_balance"];
+const TOTAL_FIELDS: &[&str] = &["total", "overall_balance"];
+const SUPPLY_FIELDS: &[&str] = &["supply", "max", "max_supply"];
+const SUPPLY_MINTED_FIELDS: &[&str] = &["supply_minted", "minted", "minted_supply"];
+const HOLDERS_FIELDS: &[&str] = &["holders", "holders_count"];
+
+impl ReferenceRow {
+    fn from_fields(fields: &HashMap<String, String>) -> Option<ReferenceRow> {
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| fields.get(*name))
+                .filter(|value| !value.is_empty())
+        };
+        let decimal = |names: &[&str]| field(names).and_then(|v| BigDecimal::from_str(v).ok());
+
+        Some(ReferenceRow {
+            ticker: canonical_ticker(field(&["ticker", "tick"])?),
+            address: field(&["address", "wallet"]).cloned(),
+            balance: decimal(BALANCE_FIELDS),
+            transfer_balance: decimal(TRANSFER_BALANCE_FIELDS),
+            total: decimal(TOTAL_FIELDS),
+            supply: decimal(SUPPLY_FIELDS),
+            supply_minted: decimal(SUPPLY_MINTED_FIELDS),
+            holders: field(HOLDERS_FIELDS).and_then(|v| v.parse().ok()),
+        })
+    }
+}
+
+/// Parses a reference dump of holders or ticker statistics. JSON dumps are either an array of
+/// objects or one object per line; CSV dumps need a header row. Numbers may be given as JSON
+/// numbers or strings.
+pub fn parse_reference(content: &str) -> Result<Vec<ReferenceRow>, String> {
+    let trimmed = content.trim_start();
+    let objects = if trimmed.starts_with('[') {
+        serde_json::from_str::<Vec<Value>>(trimmed).map_err(|err| err.to_string())?
+    } else if trimmed.starts_with('{') {
+        trimmed
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?
+    } else {
+        return parse_csv(content);
+    };
+
+    objects
+        .iter()
+        .enumerate()
+        .map(|(index, object)| {
+            let fields = object
+                .as_object()
+                .ok_or(format!("Row {} is not an object", index + 1))?
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(value) => value.clone(),
+                        Value::Null => String::new(),
+                        value => value.to_string(),
+                    };
+                    (key.to_lowercase(), value)
+                })
+                .collect();
+            ReferenceRow::from_fields(&fields).ok_or(format!("Row {} has no ticker", index + 1))
+        })
+        .collect()
+}
+
+fn parse_csv(content: &str) -> Result<Vec<ReferenceRow>, String> {
+    let mut records = csv_records(content)?.into_iter();
+    let header = records
+        .next()
+        .ok_or("Empty reference file")?
+        .into_iter()
+        .map(|name| name.to_lowercase())
+        .collect::<Vec<_>>();
+
+    records
+        .enumerate()
+        .map(|(index, record)| {
+            let fields = header.iter().cloned().zip(record).collect();
+            ReferenceRow::from_fields(&fields).ok_or(format!("Row {} has no ticker", index + 1))
+        })
+        .collect()
+}
+
+/// Splits RFC 4180 CSV into records, skipping blank lines. Quoted fields can contain commas,
+/// line breaks and quotes escaped as `""`; unquoted fields are trimmed.
+fn csv_records(content: &str) -> Result<Vec<Vec<String>>, String> {
+    fn end_field(record: &mut Vec<String>, field: &mut String, was_quoted: &mut bool) {
+        let value = std::mem::take(field);
+        record.push(if *was_quoted {
+            value
+        } else {
+            value.trim().to_string()
+        });
+        *was_quoted = false;
+    }
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let (mut quoted, mut was_quoted) = (false, false);
+    let mut chars = content.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            _ if quoted => field.push(char),
+            '"' if !was_quoted && field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+                was_quoted = true;
+            }
+            ',' => end_field(&mut record, &mut field, &mut was_quoted),
+            '\n' => {
+                end_field(&mut record, &mut field, &mut was_quoted);
+                records.push(std::mem::take(&mut record));
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            _ => field.push(char),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quoted field in reference file".to_string());
+    }
+    if !record.is_empty() || was_quoted || !field.trim().is_empty() {
+        end_field(&mut record, &mut field, &mut was_quoted);
+        records.push(record);
+    }
+    Ok(records
+        .into_iter()
+        .filter(|record| record.len() > 1 || record.first().is_some_and(|f| !f.is_empty()))
+        .collect())
+}
+
+/// Compares the reference holders of one ticker against the stored balances. Stored holders
+/// missing from the reference are reported as well.
+pub fn compare_holders(
+    ticker: &str,
+    reference: &[&ReferenceRow],
+    balances: &[Balances],
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    let mut discrepancy = |address: &str, check, expected: String, actual: String| {
+        discrepancies.push(Discrepancy {
+            ticker: ticker.to_string(),
+            address: Some(address.to_string()),
+            check,
+            expected,
+            actual,
+        })
+    };
+
+    let stored = balances
+        .iter()
+        .map(|balance| (balance.address.as_str(), balance))
+        .collect::<BTreeMap<_, _>>();
+    let mut seen = BTreeSet::new();
+
+    for row in reference {
+        let Some(address) = row.address.as_deref() else {
+            continue;
+        };
+        seen.insert(address);
+        let (balance, transfer_balance) = stored
+            .get(address)
+            .map(|b| (b.balance.clone(), b.transfer_balance.clone()))
+            .unwrap_or_default();
+
+        let checks = [
+            ("balance", &row.balance, balance.clone()),
+            (
+                "transfer_balance",
+                &row.transfer_balance,
+                transfer_balance.clone(),
+            ),
+            ("total", &row.total, balance + transfer_balance),
+        ];
+        for (check, expected, actual) in checks {
+            if let Some(expected) = expected {
+                if *expected != actual {
+                    discrepancy(address, check, expected.to_string(), actual.to_string());
+                }
+            }
+        }
+    }
+
+    for (address, balance) in stored {
+        let total = balance.balance.clone() + balance.transfer_balance.clone();
+        if !seen.contains(address) && total != BigDecimal::zero() {
+            discrepancy(address, "total", "0".to_string(), total.to_string());
+        }
+    }
+
+    discrepancies
+}
+
+/// Compares reference ticker statistics against the stored tracker.
+pub fn compare_ticker(reference: &ReferenceRow, tracker: Option<&Tracker>) -> Vec<Discrepancy> {
+    let discrepancy = |check, expected: String, actual: String| Discrepancy {
+        ticker: reference.ticker.clone(),
+        address: None,
+        check,
+        expected,
+        actual,
+    };
+    let Some(tracker) = tracker else {
+        return vec![discrepancy(
+            "deploy",
+            "deployed".to_string(),
+            "none".to_string(),
+        )];
+    };
+
+    let mut discrepancies = Vec::new();
+    if let Some(supply) = reference.supply.as_ref().filter(|s| **s != tracker.supply) {
+        discrepancies.push(discrepancy(
+            "supply",
+            supply.to_string(),
+            tracker.supply.to_string(),
+        ));
+    }
+    if let Some(minted) = reference
+        .supply_minted
+        .as_ref()
+        .filter(|m| **m != tracker.supply_minted)
+    {
+        discrepancies.push(discrepancy(
+            "supply_minted",
+            minted.to_string(),
+            tracker.supply_minted.to_string(),
+        ));
+    }
+    if let Some(holders) = reference.holders.filter(|h| *h != tracker.holders) {
+        discrepancies.push(discrepancy(
+            "holders",
+            holders.to_string(),
+            tracker.holders.to_string(),
+        ));
+    }
+    discrepancies
+}
+
+impl Indexer {
+    /// Compares a reference dump against `balances` and `tracker`, printing every difference
+    /// followed by the history rows that contributed to each mismatched balance.
+    pub(crate) fn diff(&mut self, content: &str) -> Result<usize, String> {
+        let reference = parse_reference(content)?;
+
+        let mut holders: BTreeMap<&str, Vec<&ReferenceRow>> = BTreeMap::new();
+        let mut discrepancies = Vec::new();
+        for row in &reference {
+            if row.address.is_some() {
+                holders.entry(row.ticker.as_str()).or_default().push(row);
+            } else {
+                let tracker = self
+                    .database
+                    .get_tracker(row.ticker.clone())
+                    .optional()
+                    .map_err(|err| err.to_string())?;
+                discrepancies.extend(compare_ticker(row, tracker.as_ref()));
+            }
+        }
+        for (ticker, rows) in holders {
+            let balances = self
+                .database
+                .get_balances_by_ticker(ticker.to_string())
+                .map_err(|err| err.to_string())?;
+            discrepancies.extend(compare_holders(ticker, &rows, &balances));
+        }
+
+        for discrepancy in &discrepancies {
+            println!("{}", discrepancy);
+            let Some(address) = &discrepancy.address else {
+                continue;
+            };
+            let history = self
+                .database
+                .get_history_by_address(address.clone(), discrepancy.ticker.clone())
+                .map_err(|err| err.to_string())?;
+            for row in history {
+                println!(
+                    "    {} {} {} -> {} {}{}{} ({} at {})",
+                    row.height,
+                    row.action,
+                    row.address_sender.unwrap_or_default(),
+                    row.address_receiver.unwrap_or_default(),
+                    row.amount,
+                    if row.partial_mint {
+                        format!(" of {} requested", row.amount_requested)
+                    } else {
+                        String::new()
+                    },
+                    if row.invalid { " invalid" } else { "" },
+                    row.inscription_id,
+                    row.tx_id
+                );
+            }
+        }
+        Ok(discrepancies.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::{
+        compare_holders, compare_ticker, csv_records, parse_reference, ReferenceRow,
+    };
+    use crate::models::{Balances, Tracker};
+    use bigdecimal::{BigDecimal, Zero};
+
+    #[test]
+    fn test_parse_reference_formats() {
+        let csv = "tick,address,overall_balance,available_balance\nLITE,alice,10,4\n";
+        let json =
+            r#"[{"ticker":"lite","address":"alice","overall_balance":10,"available_balance":"4"}]"#;
+        let jsonl = "{\"ticker\":\"lite\",\"address\":\"alice\",\"overall_balance\":\"10\",\"available_balance\":4}\n";
+
+        let expected = vec![ReferenceRow {
+            ticker: "lite".to_string(),
+            address: Some("alice".to_string()),
+            balance: Some(BigDecimal::from(4)),
+            total: Some(BigDecimal::from(10)),
+            ..Default::default()
+        }];
+        assert_eq!(parse_reference(csv), Ok(expected));
+        assert_eq!(parse_reference(json), parse_reference(csv));
+        assert_eq!(parse_reference(jsonl), parse_reference(csv));
+        assert!(parse_reference("address,balance\nalice,1").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_quoted_fields() {
+        let csv = "\"tick\",note,address,overall_balance\r\n\
+                   LITE,\"Alice, \"\"the\"\" holder\nof lite\",\"alice\",\"10\"\r\n\r\n";
+
+        assert_eq!(
+            parse_reference(csv),
+            Ok(vec![ReferenceRow {
+                ticker: "lite".to_string(),
+                address: Some("alice".to_string()),
+                total: Some(BigDecimal::from(10)),
+                ..Default::default()
+            }])
+        );
+        assert_eq!(
+            csv_records(csv).unwrap()[1][1],
+            "Alice, \"the\" holder\nof lite"
+        );
+        assert!(parse_reference("tick,address\n\"lite,alice\n").is_err());
+    }
+
+    #[test]
+    fn test_compare_holders_and_ticker() {
+        let reference = parse_reference(
+            "ticker,address,balance,transfer_balance\nlite,alice,10,0\nlite,bob,5,0\n",
+        )
+        .unwrap();
+        let balances = vec![
+            Balances {
+                id: 1,
+                address: "alice".to_string(),
+                ticker: "lite".to_string(),
+                balance: BigDecimal::from(8),
+                transfer_balance: BigDecimal::from(2),
+            },
+            Balances {
+                id: 2,
+                address: "carol".to_string(),
+                ticker: "lite".to_string(),
+                balance: BigDecimal::from(5),
+                transfer_balance: BigDecimal::zero(),
+            },
+        ];
+
+        let differences = compare_holders("lite", &reference.iter().collect::<Vec<_>>(), &balances)
+            .into_iter()
+            .map(|d| (d.address.unwrap(), d.check))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            differences,
+            vec![
+                ("alice".to_string(), "balance"),
+                ("alice".to_string(), "transfer_balance"),
+                ("bob".to_string(), "balance"),
+                ("carol".to_string(), "total"),
+            ]
+        );
+
+        let stats = parse_reference(r#"[{"tick":"lite","max":"1000","holders":3}]"#).unwrap();
+        let tracker = Tracker {
+            ticker: "lite".to_string(),
+            supply: BigDecimal::from(1000),
+            holders: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            compare_ticker(&stats[0], Some(&tracker))
+                .into_iter()
+                .map(|d| d.check)
+                .collect::<Vec<_>>(),
+            vec!["holders"]
+        );
+        assert_eq!(compare_ticker(&stats[0], None)[0].check, "deploy");
+    }
+}
@@ -0,0 +1,274 @@
+use crate::database::Database;
+use crate::indexer::Indexer;
+use crate::models::{Inscriptions, OutboxInsert};
+use crate::SHUTTING_DOWN;
+use bigdecimal::BigDecimal;
+use diesel::QueryResult;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventType {
+    Deploy,
+    Mint,
+    InscribeTransfer,
+    Send,
+    Burn,
+    Invalid,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::Deploy => "deploy",
+            EventType::Mint => "mint",
+            EventType::InscribeTransfer => "inscribe-transfer",
+            EventType::Send => "send",
+            EventType::Burn => "burn",
+            EventType::Invalid => "invalid",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub sequence: i64,
+    pub idempotency_key: String,
+    pub height: i64,
+    pub event: EventType,
+    pub ticker: String,
+    pub inscription_id: String,
+    pub inscription_num: i64,
+    pub tx_id: String,
+    pub address_sender: Option<String>,
+    pub address_receiver: Option<String>,
+    pub amount: Option<BigDecimal>,
+    pub timestamp: i64,
+    /// Why the operation was rejected, for `invalid` events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventSink {
+    Stdout,
+    File(String),
+    Webhook(String),
+    Notify(String),
+}
+
+impl EventSink {
+    /// Parses a comma separated sink list such as
+    /// `stdout,file:/var/lib/ord20/events.jsonl,webhook:http://localhost:8080/,notify:ord20_events`.
+    pub fn parse_list(sinks: &str) -> Result<Vec<EventSink>, String> {
+        sinks
+            .split(',')
+            .map(str::trim)
+            .filter(|sink| !sink.is_empty())
+            .map(|sink| match sink.split_once(':') {
+                None if sink == "stdout" => Ok(EventSink::Stdout),
+                Some(("file", path)) => Ok(EventSink::File(path.to_string())),
+                Some(("webhook", url)) => Ok(EventSink::Webhook(url.to_string())),
+                Some(("notify", channel)) => Ok(EventSink::Notify(channel.to_string())),
+                _ => Err(format!("Unknown event sink '{}'", sink)),
+            })
+            .collect()
+    }
+
+    pub async fn publish(
+        &self,
+        events: &[Event],
+        database: &mut Database,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            EventSink::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                for event in events {
+                    writeln!(stdout, "{}", serde_json::to_string(event)?)?;
+                }
+            }
+            EventSink::File(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                for event in events {
+                    writeln!(file, "{}", serde_json::to_string(event)?)?;
+                }
+            }
+            EventSink::Webhook(url) => {
+                let response = reqwest::Client::new().post(url).json(events).send().await?;
+                if !response.status().is_success() {
+                    return Err(format!("Failed with status code: {}", response.status()).into());
+                }
+            }
+            EventSink::Notify(channel) => {
+                for event in events {
+                    database.notify(channel, &serde_json::to_string(event)?)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Indexer {
+    /// Queues an event for the block currently being processed. Queued events are written to the
+    /// outbox together with the block.
+    pub(crate) fn record_event(
+        &mut self,
+        event: EventType,
+        inscription: &Inscriptions,
+        address_sender: Option<String>,
+        address_receiver: Option<String>,
+        amount: Option<BigDecimal>,
+    ) {
+        let (height, tx_id, timestamp) = if matches!(event, EventType::Send | EventType::Burn) {
+            (
+                inscription.spent_height.unwrap_or(inscription.height),
+                inscription.spent_tx.clone().unwrap_or_default(),
+                inscription.spent_timestamp.unwrap_or(inscription.timestamp),
+            )
+        } else {
+            (
+                inscription.height,
+                inscription.genesis_tx_id.clone(),
+                inscription.timestamp,
+            )
+        };
+        self.events.push(Event {
+            sequence: 0,
+            idempotency_key: format!("{}:{}", inscription.inscription_id, event.as_str()),
+            height,
+            event,
+            ticker: inscription.ticker.clone(),
+            inscription_id: inscription.inscription_id.clone(),
+            inscription_num: inscription.inscription_num,
+            tx_id,
+            address_sender,
+            address_receiver,
+            amount,
+            timestamp,
+            reason: None,
+        });
+    }
+
+    /// Records an `invalid` event for an operation rejected for `reason`.
+    pub(crate) fn record_invalid(
+        &mut self,
+        inscription: &Inscriptions,
+        address_sender: Option<String>,
+        address_receiver: Option<String>,
+        amount: Option<BigDecimal>,
+        reason: &str,
+    ) {
+        self.record_event(
+            EventType::Invalid,
+            inscription,
+            address_sender,
+            address_receiver,
+            amount,
+        );
+        if let Some(event) = self.events.last_mut() {
+            event.reason = Some(reason.to_string());
+        }
+    }
+
+    /// Writes a block's events to the `outbox` table. This runs inside the block's transaction
+    /// so events are stored if, and only if, the block's balance changes are committed.
+    pub(crate) fn write_outbox(&mut self, events: &[Event]) -> QueryResult<usize> {
+        if self.event_sinks.is_empty() {
+            return Ok(0);
+        }
+
+        let entries = events
+            .iter()
+            .map(|event| OutboxInsert {
+                idempotency_key: event.idempotency_key.clone(),
+                height: event.height,
+                payload: serde_json::to_string(event).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        self.database.create_outbox(&entries)
+    }
+}
+
+/// Forwards committed `outbox` rows to the configured sinks on its own database connection.
+///
+/// Rows are marked delivered only after every sink accepted them, so consumers may see an event
+/// more than once and should deduplicate on `idempotency_key`. The outbox row id is the event's
+/// `sequence` number.
+pub struct OutboxWorker {
+    pub database: Database,
+    pub event_sinks: Vec<EventSink>,
+}
+
+impl OutboxWorker {
+    pub async fn run(mut self) {
+        while !SHUTTING_DOWN.load(Ordering::Relaxed) {
+            match self.deliver().await {
+                Ok(count) if count > 0 => continue,
+                Ok(_) => {}
+                Err(err) => log::error!("Failed to deliver events: {}", err),
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn deliver(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let rows = self.database.get_undelivered_outbox(500)?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let ids = rows.iter().map(|row| row.id).collect::<Vec<_>>();
+        let mut events = Vec::new();
+        for row in rows {
+            let mut event = serde_json::from_str::<Event>(&row.payload)?;
+            event.sequence = row.id;
+            events.push(event);
+        }
+
+        for sink in &self.event_sinks {
+            if let Err(err) = sink.publish(&events, &mut self.database).await {
+                self.database.update_outbox_failed(&ids, err.to_string())?;
+                return Err(err);
+            }
+        }
+        self.database.update_outbox_delivered(&ids)?;
+        Ok(ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::events::{Event, EventSink, EventType};
+
+    #[test]
+    fn test_parse_event_sinks() {
+        assert_eq!(
+            EventSink::parse_list(
+                "stdout, file:/tmp/events.jsonl,webhook:http://localhost:8080/hook,notify:ord20"
+            ),
+            Ok(vec![
+                EventSink::Stdout,
+                EventSink::File("/tmp/events.jsonl".to_string()),
+                EventSink::Webhook("http://localhost:8080/hook".to_string()),
+                EventSink::Notify("ord20".to_string()),
+            ])
+        );
+        assert_eq!(EventSink::parse_list(""), Ok(vec![]));
+        assert!(EventSink::parse_list("kafka:events").is_err());
+    }
+
+    #[test]
+    fn test_outbox_payload_round_trip() {
+        let payload = "{\"sequence\":0,\"idempotency_key\":\"abci0:inscribe-transfer\",\"height\":10,\"event\":\"inscribe-transfer\",\"ticker\":\"lite\",\"inscription_id\":\"abci0\",\"inscription_num\":5,\"tx_id\":\"abc\",\"address_sender\":\"alice\",\"address_receiver\":null,\"amount\":\"1.5\",\"timestamp\":0}";
+
+        let event = serde_json::from_str::<Event>(payload).unwrap();
+
+        assert_eq!(event.event, EventType::InscribeTransfer);
+        assert_eq!(serde_json::to_string(&event).unwrap(), payload);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::indexer::Indexer;
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{OptionalExtension, QueryResult};
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HolderRow {
+    pub rank: usize,
+    pub address: String,
+    pub balance: BigDecimal,
+    pub transfer_balance: BigDecimal,
+    pub total: BigDecimal,
+    pub percent: BigDecimal,
+}
+
+impl HolderRow {
+    pub const CSV_HEADER: &'static str = "rank,address,balance,transfer_balance,total,percent";
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.rank, self.address, self.balance, self.transfer_balance, self.total, self.percent
+        )
+    }
+}
+
+/// Ranks holders by total balance (largest first, ties broken by address) and computes each
+/// holder's share of `supply` as a percentage. Addresses with a zero balance are dropped.
+pub fn holder_rows(
+    supply: &BigDecimal,
+    balances: Vec<(String, BigDecimal, BigDecimal)>,
+) -> Vec<HolderRow> {
+    let mut holders = balances
+        .into_iter()
+        .map(|(address, balance, transfer_balance)| {
+            let total = balance.clone() + transfer_balance.clone();
+            (address, balance, transfer_balance, total)
+        })
+        .filter(|(_, _, _, total)| *total != BigDecimal::zero())
+        .collect::<Vec<_>>();
+    holders.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| a.0.cmp(&b.0)));
+
+    holders
+        .into_iter()
+        .enumerate()
+        .map(|(index, (address, balance, transfer_balance, total))| {
+            let percent = if *supply == BigDecimal::zero() {
+                BigDecimal::zero()
+            } else {
+                (total.clone() * BigDecimal::from(100) / supply).round(8)
+            };
+            HolderRow {
+                rank: index + 1,
+                address,
+                balance,
+                transfer_balance,
+                total,
+                percent,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BalanceRow {
+    pub address: String,
+    pub ticker: String,
+    pub height: Option<i64>,
+    pub balance: BigDecimal,
+    pub transfer_balance: BigDecimal,
+    pub total: BigDecimal,
+}
+
+/// The balance of `address` for `ticker` from its `(balance, transfer_balance)`, which is zero
+/// when the address has not held the ticker.
+pub fn balance_row(
+    address: String,
+    ticker: String,
+    height: Option<i64>,
+    balances: Option<(BigDecimal, BigDecimal)>,
+) -> BalanceRow {
+    let (balance, transfer_balance) =
+        balances.unwrap_or_else(|| (BigDecimal::zero(), BigDecimal::zero()));
+    BalanceRow {
+        address,
+        ticker,
+        height,
+        total: balance.clone() + transfer_balance.clone(),
+        balance,
+        transfer_balance,
+    }
+}
+
+impl Indexer {
+    /// Returns the balance of `address` for `ticker`, as of `height` when given.
+    pub(crate) fn address_balance(
+        &mut self,
+        address: String,
+        ticker: String,
+        height: Option<i64>,
+    ) -> QueryResult<BalanceRow> {
+        let balances = match height {
+            Some(height) => self
+                .database
+                .get_balance_at_height(address.clone(), ticker.clone(), height)
+                .optional()?
+                .map(|b| (b.balance, b.transfer_balance)),
+            None => self
+                .database
+                .get_balance(address.clone(), ticker.clone())
+                .optional()?
+                .map(|b| (b.balance, b.transfer_balance)),
+        };
+        Ok(balance_row(address, ticker, height, balances))
+    }
+
+    /// Writes every holder of `ticker` (as of `height` when given) to `out`, one line per
+    /// holder, in either `csv` or `json` (line-delimited) format.
+    pub(crate) fn export_holders(
+        &mut self,
+        ticker: String,
+        height: Option<i64>,
+        format: &str,
+        out: &mut impl Write,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let tracker = self.database.get_tracker(ticker.clone())?;
+        let balances = match height {
+            Some(height) => self
+                .database
+                .get_holders_at_height(ticker, height)?
+                .into_iter()
+                .map(|b| (b.address, b.balance, b.transfer_balance))
+                .collect(),
+            None => self
+                .database
+                .get_balances_by_ticker(ticker)?
+                .into_iter()
+                .map(|b| (b.address, b.balance, b.transfer_balance))
+                .collect(),
+        };
+
+        let rows = holder_rows(&tracker.supply, balances);
+        match format {
+            "csv" => {
+                writeln!(out, "{}", HolderRow::CSV_HEADER)?;
+                for row in &rows {
+                    writeln!(out, "{}", row.to_csv())?;
+                }
+            }
+            "json" => {
+                for row in &rows {
+                    writeln!(out, "{}", serde_json::to_string(row)?)?;
+                }
+            }
+            _ => return Err(format!("Unsupported export format '{}'", format).into()),
+        }
+        Ok(rows.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::{balance_row, holder_rows};
+    use bigdecimal::{BigDecimal, Zero};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_holder_rows_rank_and_percent() {
+        let rows = holder_rows(
+            &BigDecimal::from(1000),
+            vec![
+                ("bob".to_string(), BigDecimal::from(100), BigDecimal::zero()),
+                ("carol".to_string(), BigDecimal::zero(), BigDecimal::zero()),
+                (
+                    "alice".to_string(),
+                    BigDecimal::from(50),
+                    BigDecimal::from(50),
+                ),
+                ("dave".to_string(), BigDecimal::from(1), BigDecimal::from(2)),
+            ],
+        );
+
+        assert_eq!(
+            rows.iter()
+                .map(|row| (row.rank, row.address.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "alice"), (2, "bob"), (3, "dave")]
+        );
+        assert_eq!(rows[0].percent, BigDecimal::from(10));
+        assert_eq!(rows[2].percent, BigDecimal::from_str("0.3").unwrap());
+        assert_eq!(rows[2].to_csv(), "3,dave,1,2,3,0.30000000");
+    }
+
+    #[test]
+    fn test_balance_row() {
+        let row = balance_row(
+            "alice".to_string(),
+            "lite".to_string(),
+            Some(2500000),
+            Some((BigDecimal::from(50), BigDecimal::from(25))),
+        );
+        assert_eq!(row.total, BigDecimal::from(75));
+        assert_eq!(
+            serde_json::to_string(&row).unwrap(),
+            r#"{"address":"alice","ticker":"lite","height":2500000,"balance":"50","transfer_balance":"25","total":"75"}"#
+        );
+
+        // An address that had not held the ticker by then
+        let row = balance_row("bob".to_string(), "lite".to_string(), Some(100), None);
+        assert_eq!(
+            (row.balance, row.transfer_balance, row.total),
+            (BigDecimal::zero(), BigDecimal::zero(), BigDecimal::zero())
+        );
+    }
+}
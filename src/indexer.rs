@@ -1,13 +1,19 @@
+use crate::content::{decode_body, fetch_content, inscription_body, parse_operation, ContentCache};
 use crate::database::Database;
-use crate::models::{Inscriptions, Ord20};
-use crate::ordinals::{Block, Inscription, Ordinals};
-use crate::util::{bigdecimal_fractional_count, string_to_timestamp};
+use crate::events::{Event, EventSink, EventType};
+use crate::models::{Balances, Inscriptions, Ord20, Tracker, TrackerMintStats};
+use crate::ordering::{transaction_events, OrderedEvent, TxEvent};
+use crate::ordinals::{
+    Block, ContentFetchError, Inscription, Ordinals, Transaction, TransactionInscription,
+};
+use crate::protocol::{Protocol, ResolvedDeploy, RuleVersion, TickerCheck};
+use crate::util::{bigdecimal_fractional_count, canonical_ticker, string_to_timestamp};
 use crate::SHUTTING_DOWN;
 use bigdecimal::{BigDecimal, Zero};
-use hex::decode;
+use diesel::{OptionalExtension, QueryResult};
 use lazy_static::lazy_static;
-use std::convert::Into;
 use std::env;
+use std::fmt;
 use std::string::ToString;
 use std::sync::atomic::Ordering;
 use std::sync::{mpsc, Arc};
@@ -24,10 +30,38 @@ lazy_static! {
     };
 }
 
+/// Why a block could not be indexed. Its transaction is rolled back and it is indexed again.
+#[derive(Debug)]
+pub enum IndexError {
+    Database(diesel::result::Error),
+    /// The ordinals server could not serve an inscription body. Dead-lettering it instead would
+    /// make the indexed state depend on the server's uptime.
+    ContentUnavailable(String),
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Database(err) => write!(f, "Database error: {}", err),
+            IndexError::ContentUnavailable(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for IndexError {
+    fn from(err: diesel::result::Error) -> IndexError {
+        IndexError::Database(err)
+    }
+}
+
 pub struct Indexer {
     pub ordinals: Ordinals,
     pub database: Database,
-    pub meta_protocol: String,
+    pub protocol: Protocol,
+    pub content_cache: ContentCache,
+    pub verify_interval: i32,
+    pub event_sinks: Vec<EventSink>,
+    pub events: Vec<Event>,
 }
 
 impl Indexer {
@@ -60,8 +94,10 @@ impl Indexer {
 
                 last_height = next_height;
 
-                tx.send(block_request)
-                    .expect("Failed to send block to channel");
+                // The receiver is gone once indexing stops on an error
+                if tx.send(block_request).is_err() {
+                    break;
+                }
 
                 if SHUTTING_DOWN.load(Ordering::Relaxed) {
                     break;
@@ -73,7 +109,28 @@ impl Indexer {
             match block_future.await {
                 Ok(block) => {
                     let start_time = Instant::now();
-                    let processed = self.process_block(block.as_ref().unwrap()).await;
+                    self.database
+                        .begin_transaction()
+                        .expect("Failed to start block transaction");
+                    let processed = match self.index_block(block.as_ref().unwrap()).await {
+                        Ok(processed) => processed,
+                        Err(err) => {
+                            // Nothing of the block is kept, so it is indexed again on the next run
+                            self.events.clear();
+                            self.database
+                                .rollback_transaction()
+                                .expect("Failed to roll back block transaction");
+                            log::error!(
+                                "Failed to index block {}: {}",
+                                block.as_ref().unwrap().height,
+                                err
+                            );
+                            return;
+                        }
+                    };
+                    self.database
+                        .commit_transaction()
+                        .expect("Failed to commit block transaction");
                     let elapsed_time = start_time.elapsed();
                     log::info!(
                         "Block {}/{}, Timestamp: {}, Txs: {}, Inscriptions: {}, Time: {:?}",
@@ -84,10 +141,7 @@ impl Indexer {
                         processed,
                         elapsed_time
                     );
-                    let _ = self.database.update_status(
-                        "last_height".to_string(),
-                        block.as_ref().unwrap().height.to_string(),
-                    );
+                    self.verify_at_interval(block.as_ref().unwrap().height, self.verify_interval);
                 }
                 Err(err) => {
                     eprintln!("Error: {:?}", err);
@@ -96,122 +150,199 @@ impl Indexer {
         }
     }
 
-    pub(crate) async fn process_block(&mut self, block: &Block) -> i32 {
+    /// Applies a block and records its height, state hash and events. On an error the caller
+    /// must roll back the block's transaction.
+    async fn index_block(&mut self, block: &Block) -> Result<i32, IndexError> {
+        let processed = self.process_block(block).await?;
+        self.database
+            .update_status("last_height".to_string(), block.height.to_string())?;
+        let events = std::mem::take(&mut self.events);
+        self.record_block_hash(block.height as i64, block.hash.clone(), &events)?;
+        self.write_outbox(&events)?;
+        Ok(processed)
+    }
+
+    pub(crate) async fn process_block(&mut self, block: &Block) -> Result<i32, IndexError> {
         let mut inscriptions_count = 0;
         let mut block_miner_address = "";
-        for txs in &block.transactions {
-            let address = if !txs.output_addresses.is_empty() {
-                txs.output_addresses.first().unwrap()
-            } else {
-                ""
-            };
+        let rules = self.protocol.rules_at(block.height as i64).clone();
+        for (tx_index, txs) in block.transactions.iter().enumerate() {
+            let address = txs
+                .output_addresses
+                .first()
+                .map(String::as_str)
+                .unwrap_or_default();
             if block_miner_address.is_empty() {
-                if txs.output_addresses.is_empty() {
+                if address.is_empty() {
                     log::debug!("Block coinbase sent to empty address");
                 } else {
-                    block_miner_address = txs.output_addresses.first().unwrap();
+                    block_miner_address = address;
                     log::debug!("Block Miner Address: {}", block_miner_address);
                 }
             }
             let mut fetched_up_to_index = -1;
             let mut input_offset = 0;
+            let mut spends = Vec::new();
             for (index, (input, _input_value)) in txs.inputs.iter().enumerate() {
-                // Check inputs for transfer inscription
-                if let Ok(inscription) = self.database.get_inscription_by_output(input.to_string())
-                {
-                    if inscription.action.contains("transfer")
-                        && !inscription.spent.unwrap_or_default()
-                    {
-                        if index > 0 {
-                            for backfill_index in (fetched_up_to_index + 1) as usize..index {
-                                let (backfill_input, _) = &txs.inputs[backfill_index];
-
-                                // Fetch the input_value from the database for this backfill input
-                                if let Ok(output) =
-                                    self.ordinals.get_output(backfill_input.to_string()).await
-                                {
-                                    log::info!(
-                                        "Get output for index {}:  {:?}",
-                                        backfill_input,
-                                        output
-                                    );
-                                    input_offset += output.value;
-                                    // Update fetched_up_to_index since we've now fetched this input_value
-                                    fetched_up_to_index = backfill_index as isize;
-                                } else {
-                                    log::error!(
-                                        "Failed to get output value for input: {}",
-                                        backfill_input.to_string()
-                                    );
-                                }
-                            }
-                        }
+                // Check inputs for transfer inscriptions, in the order of their sats
+                let transfers = self
+                    .database
+                    .get_inscription_by_output(input.to_string())?
+                    .into_iter()
+                    .filter(|inscription| {
+                        inscription.action.contains("transfer")
+                            && !inscription.spent.unwrap_or_default()
+                    })
+                    .collect::<Vec<_>>();
+                if transfers.is_empty() {
+                    continue;
+                }
+                if index > 0 {
+                    for backfill_index in (fetched_up_to_index + 1) as usize..index {
+                        let (backfill_input, _) = &txs.inputs[backfill_index];
 
-                        let vout =
-                            Indexer::calculate_ordinal_position(index, input_offset, &txs.outputs);
-                        let address_receiver = if vout > txs.outputs.len() - 1 {
-                            // Invalid output - Return balance to sender address
-                            inscription.genesis_address.as_str()
+                        // Fetch the input_value from the database for this backfill input
+                        if let Ok(output) =
+                            self.ordinals.get_output(backfill_input.to_string()).await
+                        {
+                            log::info!("Get output for index {}:  {:?}", backfill_input, output);
+                            input_offset += output.value;
+                            // Update fetched_up_to_index since we've now fetched this input_value
+                            fetched_up_to_index = backfill_index as isize;
                         } else {
-                            txs.output_addresses.get(vout).unwrap()
-                        };
-                        if let Ok(transfer_inscription) = self.database.update_inscription_spent(
-                            inscription.id,
-                            inscription.genesis_address.clone(),
-                            address_receiver.to_string(),
-                            txs.transaction.clone(),
-                            0,
-                            block.height as i64,
-                            string_to_timestamp(block.timestamp.as_str()).unwrap_or(0),
-                        ) {
-                            if transfer_inscription.valid.unwrap_or_default() {
-                                self.process_inscription_transfer(&transfer_inscription);
-                            }
+                            log::error!(
+                                "Failed to get output value for input: {}",
+                                backfill_input.to_string()
+                            );
                         }
                     }
                 }
+                spends.extend(
+                    transfers
+                        .into_iter()
+                        .map(|inscription| (index, input_offset, inscription)),
+                );
             }
             if !txs.inscriptions.is_empty() && address.is_empty() {
                 log::warn!("Empty address on tx {}", txs.transaction);
             }
-            for tx_inscription in &txs.inscriptions {
-                let inscription = Inscription {
-                    number: tx_inscription.inscription_number,
-                    offset: 0,
-                    genesis_fee: 0,
-                    genesis_height: block.height as i64,
-                    genesis_transaction: txs.transaction.clone(),
-                    inscription_id: tx_inscription.inscription_id.clone(),
-                    output: tx_inscription.inscription_id.replace('i', ":"),
-                    location: "".to_string(),
-                    address: "".to_string(),
-                    genesis_address: address.to_string(),
-                    content_type: tx_inscription.content_type.as_ref().map(|s| {
-                        decode(s.as_bytes())
-                            .map(|decoded| String::from_utf8_lossy(&decoded).into_owned())
-                            .unwrap_or_else(|_| String::new())
-                    }),
-                    timestamp: block.timestamp.clone(),
-                };
-                if inscription.number > 0
-                    && inscription.content_type.as_ref().is_some_and(|ct| {
-                        ct.contains("text/plain") || ct.contains("application/json")
-                    })
-                {
-                    if let Some(inscription) = self.add_inscription(
+
+            for OrderedEvent { tx_offset, event } in transaction_events(txs, spends) {
+                match event {
+                    TxEvent::Spend {
                         inscription,
-                        decode(tx_inscription.content.clone().unwrap())
-                            .map(|s| String::from_utf8_lossy(s.as_slice()).into_owned())
-                            .unwrap(),
-                    ) {
-                        log::debug!("Process Inscription: {:?}", inscription);
-                        inscriptions_count += 1;
-                        self.process_inscription(&inscription);
+                        vout,
+                        output_offset,
+                    } => {
+                        let receiver =
+                            Indexer::spend_receiver(txs, vout, &inscription.genesis_address);
+                        let burned = receiver.is_none();
+                        let address_receiver = receiver.unwrap_or_default();
+                        let transfer_inscription = self.database.update_inscription_spent(
+                            inscription.id,
+                            inscription.genesis_address.clone(),
+                            address_receiver.to_string(),
+                            txs.transaction.clone(),
+                            txs.outputs.get(vout).map(|(output, _)| output.clone()),
+                            output_offset as i64,
+                            block.height as i64,
+                            string_to_timestamp(block.timestamp.as_str()).unwrap_or(0),
+                            tx_index as i64,
+                            tx_offset as i64,
+                            burned,
+                        )?;
+                        if transfer_inscription.valid.unwrap_or_default() {
+                            self.process_inscription_transfer(&transfer_inscription)?;
+                        }
+                    }
+                    TxEvent::Reveal {
+                        inscription: tx_inscription,
+                        output,
+                        output_offset,
+                    } => {
+                        let inscription = Inscription {
+                            number: tx_inscription.inscription_number,
+                            offset: output_offset as i64,
+                            genesis_fee: 0,
+                            genesis_height: block.height as i64,
+                            genesis_transaction: txs.transaction.clone(),
+                            inscription_id: tx_inscription.inscription_id.clone(),
+                            output,
+                            location: "".to_string(),
+                            address: "".to_string(),
+                            genesis_address: address.to_string(),
+                            content_type: tx_inscription.decoded_content_type(),
+                            timestamp: block.timestamp.clone(),
+                        };
+                        if !rules
+                            .accepts_reveal(inscription.number, inscription.content_type.as_deref())
+                        {
+                            continue;
+                        }
+                        let body = if tx_inscription.content.is_some() {
+                            inscription_body(
+                                tx_inscription.content.as_deref(),
+                                rules.content_encoding(tx_inscription.content_encoding.as_deref()),
+                            )
+                        } else {
+                            // Block data leaves out large bodies
+                            match fetch_content(
+                                &mut self.ordinals,
+                                &mut self.content_cache,
+                                &tx_inscription.inscription_id,
+                            )
+                            .await
+                            {
+                                Ok((content, content_encoding)) => decode_body(
+                                    &content,
+                                    rules.content_encoding(content_encoding.as_deref()),
+                                ),
+                                Err(ContentFetchError::Unavailable(err)) => {
+                                    return Err(IndexError::ContentUnavailable(format!(
+                                        "Content of {} is unavailable: {}",
+                                        tx_inscription.inscription_id, err
+                                    )));
+                                }
+                                Err(err) => Err(format!("Failed to fetch content: {}", err)),
+                            }
+                        };
+                        match body.and_then(|content| {
+                            self.parse_inscription(&content, block.timestamp.as_str())
+                        }) {
+                            Ok(Some((ord20, timestamp))) => {
+                                let inscription = self.add_inscription(
+                                    inscription,
+                                    ord20,
+                                    timestamp,
+                                    tx_index as i64,
+                                    tx_offset as i64,
+                                    tx_inscription,
+                                )?;
+                                log::debug!("Process Inscription: {:?}", inscription);
+                                inscriptions_count += 1;
+                                self.process_inscription(&inscription)?;
+                            }
+                            Ok(None) => {}
+                            Err(err) => {
+                                log::warn!(
+                                    "Dead-lettering inscription {}: {}",
+                                    tx_inscription.inscription_id,
+                                    err
+                                );
+                                self.database.create_dead_letter(
+                                    block.height as i64,
+                                    txs.transaction.clone(),
+                                    tx_inscription.inscription_id.clone(),
+                                    serde_json::to_string(tx_inscription).unwrap_or_default(),
+                                    err,
+                                )?;
+                            }
+                        }
                     }
                 }
             }
         }
-        inscriptions_count
+        Ok(inscriptions_count)
     }
 
     pub(crate) fn calculate_ordinal_position(
@@ -222,9 +353,9 @@ impl Indexer {
         let mut output_index = 0;
         let mut output_count = 0;
 
-        // Skip check if ordinal input is first input in transaction
+        // Skip check if ordinal is the first sat of the transaction
         // Assumptions: ordinal inscribed at offset 0 of input is on output 0
-        if input_index == 0 {
+        if input_index == 0 && ordinal_offset == 0 {
             return output_index;
         }
 
@@ -239,220 +370,501 @@ impl Indexer {
         output_index
     }
 
-    pub(crate) fn add_inscription(
-        &mut self,
-        inscription: Inscription,
-        content: String,
-    ) -> Option<Inscriptions> {
-        if let Ok(ord20) = serde_json::from_str::<Ord20>(&content) {
-            if !ord20.p.contains(self.meta_protocol.as_str()) {
-                return None;
-            }
-            let inscription_id = if inscription.inscription_id.is_empty() {
-                format!("{}i{}", inscription.genesis_transaction, inscription.output)
+    /// The address a transfer inscription spent to output `vout` of `txs` is sent to. Outputs
+    /// without a spendable address, such as `OP_RETURN` outputs, burn it and return `None`, and
+    /// spent as fee it returns to `genesis_address`.
+    pub(crate) fn spend_receiver<'a>(
+        txs: &'a Transaction,
+        vout: usize,
+        genesis_address: &'a str,
+    ) -> Option<&'a str> {
+        if vout >= txs.outputs.len() {
+            return Some(genesis_address);
+        }
+        txs.output_addresses
+            .get(vout)
+            .map(String::as_str)
+            .filter(|address| !address.is_empty())
+    }
+
+    /// The sender's transfer balance after `amount` is burned from it, and the change in holders.
+    pub(crate) fn burn_from(sender: &Balances, amount: &BigDecimal) -> (BigDecimal, i64) {
+        let transfer_balance = sender.transfer_balance.clone() - amount;
+        let holders_change =
+            if sender.balance == BigDecimal::zero() && transfer_balance == BigDecimal::zero() {
+                -1
             } else {
-                inscription.inscription_id
+                0
             };
-            // Add to inscription database
-            return self
-                .database
-                .create_inscription(
-                    inscription.genesis_transaction,
-                    inscription.genesis_address,
-                    inscription_id,
-                    inscription.number,
-                    inscription.genesis_height,
-                    inscription.output,
-                    string_to_timestamp(inscription.timestamp.as_str()).unwrap(),
-                    ord20.tick.to_lowercase(),
-                    ord20.op,
-                    ord20.max,
-                    ord20.lim,
-                    ord20.dec,
-                    ord20.amt,
-                    0,
-                )
-                .ok();
+        (transfer_balance, holders_change)
+    }
+
+    /// The amount a valid mint of `amount` credits once `supply_minted` of `supply` is minted,
+    /// and whether it was capped to the remaining supply.
+    pub(crate) fn mint_credit(
+        supply: &BigDecimal,
+        supply_minted: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> (BigDecimal, bool) {
+        let remaining = supply - supply_minted;
+        if *amount > remaining {
+            (remaining, true)
+        } else {
+            (amount.clone(), false)
         }
-        log::info!("Error processing content {}", content);
-        None
     }
 
-    pub(crate) fn process_inscription(&mut self, inscription: &Inscriptions) {
-        let ticker = self.database.get_tracker(inscription.ticker.clone());
+    /// The offset of the sat at `ordinal_offset` within output `vout`.
+    pub(crate) fn calculate_output_offset(
+        vout: usize,
+        ordinal_offset: u64,
+        outputs: &[(String, u64)],
+    ) -> u64 {
+        ordinal_offset
+            - outputs
+                .iter()
+                .take(vout)
+                .map(|(_, value)| value)
+                .sum::<u64>()
+    }
 
-        if &inscription.action == "deploy" {
-            if ticker.is_err() {
-                let decimal = inscription.decimal.unwrap_or(18);
+    /// Parses an inscription body as an operation of the indexed protocol, with the timestamp of
+    /// its block. Bodies of other protocols are `Ok(None)`; malformed ones are errors.
+    pub(crate) fn parse_inscription(
+        &self,
+        content: &[u8],
+        timestamp: &str,
+    ) -> Result<Option<(Ord20, i64)>, String> {
+        let content = String::from_utf8_lossy(content);
+        let Some(ord20) = parse_operation(&content, &self.protocol.meta_protocol)? else {
+            log::debug!("Ignoring content {}", content);
+            return Ok(None);
+        };
+        let timestamp =
+            string_to_timestamp(timestamp).ok_or(format!("Invalid timestamp '{}'", timestamp))?;
+        Ok(Some((ord20, timestamp)))
+    }
 
-                if !(0..=18).contains(&decimal) {
-                    return;
-                }
+    pub(crate) fn add_inscription(
+        &mut self,
+        inscription: Inscription,
+        ord20: Ord20,
+        timestamp: i64,
+        tx_index: i64,
+        tx_offset: i64,
+        tx_inscription: &TransactionInscription,
+    ) -> QueryResult<Inscriptions> {
+        let inscription_id = if inscription.inscription_id.is_empty() {
+            format!("{}i{}", inscription.genesis_transaction, inscription.output)
+        } else {
+            inscription.inscription_id
+        };
+        let sat = tx_inscription.sat.map(|sat| sat as i64);
+        // Only a reported satpoint is known to be the inscribed sat
+        let satpoint = tx_inscription
+            .satpoint
+            .as_ref()
+            .map(|_| (inscription.output.clone(), inscription.offset));
+        let invalid_reason = Indexer::invalid_reason(&tx_inscription.charms, || {
+            Indexer::inscribed_before(&mut self.database, &inscription_id, sat, satpoint.as_ref())
+        })?;
+        // Add to inscription database
+        self.database.create_inscription(
+            inscription.genesis_transaction,
+            inscription.genesis_address,
+            inscription_id,
+            inscription.number,
+            inscription.genesis_height,
+            inscription.output,
+            inscription.offset,
+            timestamp,
+            canonical_ticker(&ord20.tick),
+            ord20.tick,
+            ord20.op,
+            ord20.max,
+            ord20.lim,
+            ord20.dec,
+            ord20.amt,
+            0,
+            tx_index,
+            tx_offset,
+            tx_inscription.parent.clone(),
+            ord20.self_mint.as_deref() == Some("true"),
+            sat,
+            invalid_reason,
+        )
+    }
 
-                #[allow(clippy::needless_as_bytes)]
-                if inscription.ticker.as_bytes().len() != 4 {
-                    return;
-                }
+    /// Why a revealed inscription does not count, if it does not. Only the first inscription on a
+    /// sat counts, so one with ord's `reinscription` charm or that `inscribed_before` finds an
+    /// earlier inscription for is a reinscription.
+    pub(crate) fn invalid_reason(
+        charms: &[String],
+        inscribed_before: impl FnOnce() -> QueryResult<bool>,
+    ) -> QueryResult<Option<String>> {
+        let reinscription =
+            charms.iter().any(|charm| charm == "reinscription") || inscribed_before()?;
+        Ok(reinscription.then(|| "reinscription".to_string()))
+    }
 
-                if inscription.supply.clone().unwrap_or_default() <= BigDecimal::zero()
-                    || inscription
-                        .supply
-                        .clone()
-                        .unwrap_or(BigDecimal::from(i64::MAX - 1))
-                        > i64::MAX.into()
-                    || inscription.limit_mint.clone().unwrap_or_default() < BigDecimal::zero()
-                {
-                    return;
-                }
+    /// Whether an inscription other than `inscription_id` is stored on `sat`, or is or was on
+    /// the sat at `satpoint`.
+    pub(crate) fn inscribed_before(
+        database: &mut Database,
+        inscription_id: &str,
+        sat: Option<i64>,
+        satpoint: Option<&(String, i64)>,
+    ) -> QueryResult<bool> {
+        Ok(database
+            .get_inscriptions_near_sat(
+                inscription_id.to_string(),
+                sat,
+                satpoint.map(|(output, _)| output.clone()),
+            )?
+            .iter()
+            .any(|stored| Indexer::inscribes_sat(stored, sat, satpoint)))
+    }
 
-                self.database.create_tracker(
-                    inscription.ticker.clone(),
-                    inscription.inscription_id.clone(),
-                    inscription.inscription_num,
-                    inscription.supply.clone().unwrap_or_default(),
-                    inscription.limit_mint.clone().unwrap_or_default(),
-                    decimal,
-                );
-            }
-        } else if &inscription.action == "mint" {
-            if let Ok(ticker) = ticker {
-                let limit = ticker.limit_mint;
+    /// Whether `stored` is or was on the sat at `satpoint`, or is on `sat`.
+    pub(crate) fn inscribes_sat(
+        stored: &Inscriptions,
+        sat: Option<i64>,
+        satpoint: Option<&(String, i64)>,
+    ) -> bool {
+        satpoint.is_some_and(|(output, offset)| {
+            (stored.output == *output && stored.output_offset == *offset)
+                || (stored.spent_output.as_ref() == Some(output)
+                    && stored.spent_offset == Some(*offset))
+        }) || sat.is_some_and(|sat| stored.sat == Some(sat))
+    }
 
-                let amount = inscription.amount.clone().unwrap_or_default();
+    /// Why `inscription` is skipped instead of applied, if it is.
+    pub(crate) fn skip_reason(inscription: &Inscriptions) -> Option<&str> {
+        inscription.invalid_reason.as_deref()
+    }
 
-                if amount <= BigDecimal::zero()
-                    || bigdecimal_fractional_count(amount.clone()) > ticker.decimals as u32
-                {
-                    return;
+    pub(crate) fn process_inscription(&mut self, inscription: &Inscriptions) -> QueryResult<()> {
+        if let Some(reason) = Indexer::skip_reason(inscription) {
+            log::debug!(
+                "Skipping inscription {}: {}",
+                inscription.inscription_id,
+                reason
+            );
+            return Ok(());
+        }
+        let ticker = self
+            .database
+            .get_tracker(inscription.ticker.clone())
+            .optional()?;
+
+        if &inscription.action == "deploy" {
+            let rules = self.protocol.rules_at(inscription.height);
+            match Indexer::deploy_rejection(rules, ticker.as_ref(), inscription) {
+                Ok((deploy, self_mint)) => {
+                    self.database.create_tracker(
+                        inscription.ticker.clone(),
+                        inscription.original_ticker.clone(),
+                        inscription.inscription_id.clone(),
+                        inscription.inscription_num,
+                        deploy.supply.clone(),
+                        deploy.limit_mint,
+                        deploy.decimals,
+                        self_mint,
+                    )?;
+                    self.record_event(
+                        EventType::Deploy,
+                        inscription,
+                        None,
+                        Some(inscription.genesis_address.clone()),
+                        Some(deploy.supply),
+                    );
+                }
+                Err(reason) => self.record_invalid(
+                    inscription,
+                    None,
+                    Some(inscription.genesis_address.clone()),
+                    inscription.supply.clone(),
+                    reason,
+                ),
+            }
+        } else if &inscription.action == "mint" {
+            if let Some(reason) = Indexer::mint_rejection(ticker.as_ref(), inscription) {
+                if let Some(ticker) = &ticker {
+                    self.database
+                        .update_tracker_invalid_mints(ticker.id, ticker.invalid_mints + 1)?;
                 }
+                self.record_invalid(
+                    inscription,
+                    None,
+                    Some(inscription.genesis_address.clone()),
+                    inscription.amount.clone(),
+                    reason,
+                );
+                return Ok(());
+            }
+            let Some(ticker) = ticker else {
+                return Ok(());
+            };
+            let amount = inscription.amount.clone().unwrap_or_default();
 
-                if (limit == BigDecimal::zero() || limit >= amount)
-                    && ticker.supply != ticker.supply_minted.clone()
-                {
-                    if ticker.supply_minted.clone() == BigDecimal::zero() {
-                        let _ = self
-                            .database
-                            .update_tracker_mint_start(ticker.id, inscription.inscription_num);
-                    }
-                    if ticker.supply_minted.clone() + amount.clone() >= ticker.supply {
-                        let _ = self
-                            .database
-                            .update_tracker_mint_end(ticker.id, inscription.inscription_num);
-                    }
+            if ticker.supply_minted.clone() == BigDecimal::zero() {
+                self.database
+                    .update_tracker_mint_start(ticker.id, inscription.inscription_num)?;
+            }
+            if ticker.supply_minted.clone() + amount.clone() >= ticker.supply {
+                self.database
+                    .update_tracker_mint_end(ticker.id, inscription.inscription_num)?;
+            }
 
-                    let mut mint_balance = amount.clone();
-                    let mut new_supply = ticker.supply_minted + mint_balance.clone();
+            let (credited, partial_mint) =
+                Indexer::mint_credit(&ticker.supply, &ticker.supply_minted, &amount);
+            let mut mint_balance = credited.clone();
 
-                    if new_supply > ticker.supply {
-                        mint_balance = amount.clone() - (new_supply - ticker.supply.clone());
-                        new_supply = ticker.supply;
-                    }
+            self.database.update_tracker_minted(
+                ticker.id,
+                ticker.supply_minted.clone() + credited.clone(),
+            )?;
 
-                    let _ = self.database.update_tracker_minted(ticker.id, new_supply);
+            let mut transfer_balance = BigDecimal::zero();
 
-                    let mut transfer_balance = BigDecimal::zero();
+            if let Some(account) = self
+                .database
+                .get_balance(inscription.genesis_address.clone(), ticker.ticker.clone())
+                .optional()?
+            {
+                mint_balance = account.balance + mint_balance.clone();
+                transfer_balance = account.transfer_balance + transfer_balance;
+            } else {
+                self.database
+                    .create_balance(inscription.genesis_address.clone(), ticker.ticker.clone())?;
+                self.database
+                    .update_tracker_holders(ticker.id, ticker.holders + 1)?;
+            }
 
-                    if let Ok(account) = self
-                        .database
-                        .get_balance(inscription.genesis_address.clone(), ticker.ticker.clone())
-                    {
-                        mint_balance = account.balance + mint_balance.clone();
-                        transfer_balance = account.transfer_balance + transfer_balance;
-                    } else {
-                        let _ = self.database.create_balance(
-                            inscription.genesis_address.clone(),
-                            ticker.ticker.clone(),
-                        );
-                        let _ = self
-                            .database
-                            .update_tracker_holders(ticker.id, ticker.holders + 1);
-                    }
+            self.database.update_balance(
+                inscription.genesis_address.clone(),
+                ticker.ticker.clone(),
+                mint_balance,
+                transfer_balance,
+                inscription.height,
+            )?;
 
-                    let _ = self.database.update_balance(
-                        inscription.genesis_address.clone(),
-                        ticker.ticker.clone(),
-                        mint_balance,
-                        transfer_balance,
-                    );
+            let new_minter = !self
+                .database
+                .has_minted(inscription.genesis_address.clone(), ticker.ticker.clone())?;
+            self.database.update_tracker_mint_stats(
+                ticker.id,
+                &TrackerMintStats {
+                    valid_mints: ticker.valid_mints + 1,
+                    unique_minters: ticker.unique_minters + new_minter as i64,
+                    first_mint_height: ticker.first_mint_height.or(Some(inscription.height)),
+                    first_mint_timestamp: ticker
+                        .first_mint_timestamp
+                        .or(Some(inscription.timestamp)),
+                    last_mint_height: Some(inscription.height),
+                    last_mint_timestamp: Some(inscription.timestamp),
+                },
+            )?;
+            self.database.add_mint_activity(
+                ticker.ticker.clone(),
+                inscription.height,
+                inscription.timestamp,
+                credited.clone(),
+            )?;
+
+            self.database.create_history(
+                "".to_string(),
+                inscription.genesis_address.clone(),
+                credited.clone(),
+                amount.clone(),
+                ticker.ticker.clone(),
+                inscription.action.clone(),
+                false,
+                partial_mint,
+                inscription.inscription_id.clone(),
+                inscription.inscription_num,
+                inscription.genesis_tx_id.clone(),
+                inscription.height,
+                inscription.timestamp,
+            )?;
+            self.record_event(
+                EventType::Mint,
+                inscription,
+                None,
+                Some(inscription.genesis_address.clone()),
+                Some(credited),
+            );
+        } else if &inscription.action == "transfer" {
+            let account = match &ticker {
+                Some(ticker) => self
+                    .database
+                    .get_balance(inscription.genesis_address.clone(), ticker.ticker.clone())
+                    .optional()?,
+                None => None,
+            };
+            let rejection =
+                Indexer::transfer_rejection(ticker.as_ref(), account.as_ref(), inscription);
+            let valid = rejection.is_none();
 
-                    let _ = self.database.create_history(
-                        "".to_string(),
+            if let (Some(ticker), Some(amount)) = (&ticker, &inscription.amount) {
+                if let (Some(account), true) = (&account, valid) {
+                    self.database.update_balance(
                         inscription.genesis_address.clone(),
-                        inscription.amount.clone().unwrap(),
                         ticker.ticker.clone(),
-                        inscription.action.clone(),
-                        false,
-                        inscription.inscription_id.clone(),
-                        inscription.inscription_num,
-                        inscription.genesis_tx_id.clone(),
+                        account.balance.clone() - amount.clone(),
+                        account.transfer_balance.clone() + amount.clone(),
                         inscription.height,
-                        inscription.timestamp,
-                    );
+                    )?;
                 }
+
+                self.database.create_history(
+                    inscription.genesis_address.clone(),
+                    "".to_string(),
+                    amount.clone(),
+                    amount.clone(),
+                    ticker.ticker.clone(),
+                    inscription.action.clone(),
+                    !valid,
+                    false,
+                    inscription.inscription_id.clone(),
+                    inscription.inscription_num,
+                    inscription.genesis_tx_id.clone(),
+                    inscription.height,
+                    inscription.timestamp,
+                )?;
             }
-        } else if &inscription.action == "transfer" && inscription.amount.is_some() {
-            if let Ok(ticker) = ticker {
-                if let Ok(account) = self
-                    .database
-                    .get_balance(inscription.genesis_address.clone(), ticker.ticker.clone())
-                {
-                    let amount = inscription.amount.clone().unwrap();
 
-                    if amount <= BigDecimal::zero()
-                        || bigdecimal_fractional_count(amount.clone()) > ticker.decimals as u32
-                    {
-                        return;
-                    }
+            match rejection {
+                Some(reason) => self.record_invalid(
+                    inscription,
+                    Some(inscription.genesis_address.clone()),
+                    None,
+                    inscription.amount.clone(),
+                    reason,
+                ),
+                None => self.record_event(
+                    EventType::InscribeTransfer,
+                    inscription,
+                    Some(inscription.genesis_address.clone()),
+                    None,
+                    inscription.amount.clone(),
+                ),
+            }
 
-                    let mut invalid = false;
-                    if account.balance >= amount {
-                        let new_balance = account.balance - amount.clone();
-                        let transfer_balance = account.transfer_balance + amount.clone();
+            self.database
+                .update_inscription_valid(inscription.id, valid)?;
+        }
+        Ok(())
+    }
 
-                        let _ = self.database.update_balance(
-                            inscription.genesis_address.clone(),
-                            ticker.ticker.clone(),
-                            new_balance,
-                            transfer_balance,
-                        );
-                    } else {
-                        invalid = true;
-                    }
+    /// Resolves a deploy, or returns why it is rejected: only the first deploy of a ticker
+    /// counts, and its ticker and values must pass the rules at its height.
+    pub(crate) fn deploy_rejection(
+        rules: &RuleVersion,
+        ticker: Option<&Tracker>,
+        inscription: &Inscriptions,
+    ) -> Result<(ResolvedDeploy, bool), &'static str> {
+        if ticker.is_some() {
+            return Err("ticker already deployed");
+        }
+        let TickerCheck::Valid { self_mint } =
+            rules.check_ticker(&inscription.original_ticker, inscription.self_mint)
+        else {
+            return Err("invalid ticker");
+        };
+        rules
+            .resolve_deploy(
+                inscription.supply.clone(),
+                inscription.limit_mint.clone(),
+                inscription.decimal,
+                self_mint,
+            )
+            .map(|deploy| (deploy, self_mint))
+            .ok_or("invalid deploy values")
+    }
 
-                    let _ = self.database.create_history(
-                        inscription.genesis_address.clone(),
-                        "".to_string(),
-                        inscription.amount.clone().unwrap(),
-                        ticker.ticker.clone(),
-                        inscription.action.clone(),
-                        invalid,
-                        inscription.inscription_id.clone(),
-                        inscription.inscription_num,
-                        inscription.genesis_tx_id.clone(),
-                        inscription.height,
-                        inscription.timestamp,
-                    );
+    /// Returns why a mint is rejected, if it is.
+    pub(crate) fn mint_rejection(
+        ticker: Option<&Tracker>,
+        inscription: &Inscriptions,
+    ) -> Option<&'static str> {
+        let Some(ticker) = ticker else {
+            return Some("ticker not deployed");
+        };
+        let amount = inscription.amount.clone().unwrap_or_default();
 
-                    let _ = self
-                        .database
-                        .update_inscription_valid(inscription.id, !invalid);
-                }
-            }
+        // Self-minted tickers can only be minted by children of their deploy inscription
+        if ticker.self_mint && inscription.parent_id.as_ref() != Some(&ticker.deploy_inscription) {
+            Some("not a child of the deploy inscription")
+        } else if amount <= BigDecimal::zero() {
+            Some("invalid amount")
+        } else if bigdecimal_fractional_count(amount.clone()) > ticker.decimals as u32 {
+            Some("too many decimals")
+        } else if amount > ticker.limit_mint {
+            Some("amount over the mint limit")
+        } else if ticker.supply == ticker.supply_minted {
+            Some("supply fully minted")
+        } else {
+            None
         }
     }
 
-    pub(crate) fn process_inscription_transfer(&mut self, inscription: &Inscriptions) {
+    /// Returns why a transfer inscription is rejected, if it is. `account` is the balance of
+    /// the inscribing address in the ticker.
+    pub(crate) fn transfer_rejection(
+        ticker: Option<&Tracker>,
+        account: Option<&Balances>,
+        inscription: &Inscriptions,
+    ) -> Option<&'static str> {
+        let Some(amount) = &inscription.amount else {
+            return Some("missing amount");
+        };
+        let Some(ticker) = ticker else {
+            return Some("ticker not deployed");
+        };
+
+        if *amount <= BigDecimal::zero() {
+            Some("invalid amount")
+        } else if bigdecimal_fractional_count(amount.clone()) > ticker.decimals as u32 {
+            Some("too many decimals")
+        } else if account.is_none_or(|account| account.balance < *amount) {
+            Some("insufficient balance")
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn process_inscription_transfer(
+        &mut self,
+        inscription: &Inscriptions,
+    ) -> QueryResult<()> {
+        let (Some(address_sender), Some(address_receiver)) = (
+            inscription.address_sender.clone(),
+            inscription.address_receiver.clone(),
+        ) else {
+            log::error!(
+                "Transfer inscription {} has no sender or receiver",
+                inscription.inscription_id
+            );
+            return Ok(());
+        };
         let amount = inscription.amount.clone().unwrap_or_default();
+        let height = inscription.spent_height.unwrap_or(inscription.height);
+
+        if inscription.burned {
+            return self.process_inscription_burn(inscription, address_sender, amount, height);
+        }
 
         let mut receiver_balance_current = BigDecimal::zero();
         let mut receiver_transfer_balance_current = BigDecimal::zero();
 
         let mut holders_change = 0;
 
-        if let Ok(receiver_balance) = self.database.get_balance(
-            inscription.address_receiver.clone().unwrap(),
-            inscription.ticker.clone(),
-        ) {
+        if let Some(receiver_balance) = self
+            .database
+            .get_balance(address_receiver.clone(), inscription.ticker.clone())
+            .optional()?
+        {
             if receiver_balance.balance.clone() == BigDecimal::zero()
                 && receiver_balance.transfer_balance.clone() == BigDecimal::zero()
             {
@@ -463,19 +875,18 @@ impl Indexer {
             receiver_transfer_balance_current = receiver_balance.transfer_balance;
         } else {
             // If no existing entry exists then create a new one
-            let _ = self.database.create_balance(
-                inscription.address_receiver.clone().unwrap(),
-                inscription.ticker.clone(),
-            );
+            self.database
+                .create_balance(address_receiver.clone(), inscription.ticker.clone())?;
             holders_change = 1;
         }
 
-        if let Ok(sender_balance) = self.database.get_balance(
-            inscription.address_sender.clone().unwrap(),
-            inscription.ticker.clone(),
-        ) {
+        if let Some(sender_balance) = self
+            .database
+            .get_balance(address_sender.clone(), inscription.ticker.clone())
+            .optional()?
+        {
             let sender_transfer_balance_new = sender_balance.transfer_balance - amount.clone();
-            let receiver_balance_new = receiver_balance_current + amount;
+            let receiver_balance_new = receiver_balance_current + amount.clone();
 
             if sender_balance.balance.clone() == BigDecimal::zero()
                 && sender_transfer_balance_new.clone() == BigDecimal::zero()
@@ -483,62 +894,141 @@ impl Indexer {
                 holders_change -= 1;
             }
 
-            if inscription
-                .address_sender
-                .clone()
-                .unwrap()
-                .eq(&inscription.address_receiver.clone().unwrap())
-            {
-                let _ = self.database.update_balance(
-                    inscription.address_sender.clone().unwrap(),
+            if address_sender == address_receiver {
+                self.database.update_balance(
+                    address_sender.clone(),
                     inscription.ticker.clone(),
                     receiver_balance_new,
                     sender_transfer_balance_new,
-                );
+                    height,
+                )?;
             } else {
-                let _ = self.database.update_balance(
-                    inscription.address_sender.clone().unwrap(),
+                self.database.update_balance(
+                    address_sender.clone(),
                     inscription.ticker.clone(),
                     sender_balance.balance,
                     sender_transfer_balance_new,
-                );
-                let _ = self.database.update_balance(
-                    inscription.address_receiver.clone().unwrap(),
+                    height,
+                )?;
+                self.database.update_balance(
+                    address_receiver.clone(),
                     inscription.ticker.clone(),
                     receiver_balance_new,
                     receiver_transfer_balance_current,
-                );
+                    height,
+                )?;
             }
 
-            if let Ok(tracker) = self.database.get_tracker(inscription.ticker.clone()) {
-                let _ = self
-                    .database
-                    .update_tracker_holders(tracker.id, tracker.holders + holders_change);
-                let _ = self
-                    .database
-                    .update_tracker_transactions(tracker.id, tracker.transactions + 1);
+            if let Some(tracker) = self
+                .database
+                .get_tracker(inscription.ticker.clone())
+                .optional()?
+            {
+                self.database
+                    .update_tracker_holders(tracker.id, tracker.holders + holders_change)?;
+                self.database
+                    .update_tracker_transactions(tracker.id, tracker.transactions + 1)?;
             }
 
-            let _ = self.database.create_history(
-                inscription.address_sender.clone().unwrap(),
-                inscription.address_receiver.clone().unwrap(),
-                inscription.amount.clone().unwrap(),
+            self.database.create_history(
+                address_sender.clone(),
+                address_receiver.clone(),
+                amount.clone(),
+                amount.clone(),
                 inscription.ticker.clone(),
                 "send".to_string(),
                 false,
+                false,
                 inscription.inscription_id.clone(),
                 inscription.inscription_num,
                 inscription.genesis_tx_id.clone(),
                 inscription.height,
                 inscription.timestamp,
+            )?;
+            self.record_event(
+                EventType::Send,
+                inscription,
+                inscription.address_sender.clone(),
+                inscription.address_receiver.clone(),
+                inscription.amount.clone(),
             );
         }
+        Ok(())
+    }
+
+    /// Removes a transfer inscription sent to an output without a spendable address from the
+    /// sender's transfer balance and adds it to the ticker's burned total. Nobody is credited.
+    fn process_inscription_burn(
+        &mut self,
+        inscription: &Inscriptions,
+        address_sender: String,
+        amount: BigDecimal,
+        height: i64,
+    ) -> QueryResult<()> {
+        let Some(sender_balance) = self
+            .database
+            .get_balance(address_sender.clone(), inscription.ticker.clone())
+            .optional()?
+        else {
+            return Ok(());
+        };
+        let (sender_transfer_balance_new, holders_change) =
+            Indexer::burn_from(&sender_balance, &amount);
+        self.database.update_balance(
+            address_sender.clone(),
+            inscription.ticker.clone(),
+            sender_balance.balance,
+            sender_transfer_balance_new,
+            height,
+        )?;
+
+        if let Some(tracker) = self
+            .database
+            .get_tracker(inscription.ticker.clone())
+            .optional()?
+        {
+            self.database
+                .update_tracker_holders(tracker.id, tracker.holders + holders_change)?;
+            self.database
+                .update_tracker_transactions(tracker.id, tracker.transactions + 1)?;
+            self.database
+                .update_tracker_burned(tracker.id, tracker.burned + amount.clone())?;
+        }
+
+        self.database.create_history(
+            address_sender.clone(),
+            "".to_string(),
+            amount.clone(),
+            amount.clone(),
+            inscription.ticker.clone(),
+            "burn".to_string(),
+            false,
+            false,
+            inscription.inscription_id.clone(),
+            inscription.inscription_num,
+            inscription.genesis_tx_id.clone(),
+            inscription.height,
+            inscription.timestamp,
+        )?;
+        self.record_event(
+            EventType::Burn,
+            inscription,
+            Some(address_sender),
+            None,
+            Some(amount),
+        );
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::indexer::Indexer;
+    use crate::models::{Balances, Inscriptions, Tracker};
+    use crate::ordinals::Transaction;
+    use crate::protocol::Protocol;
+    use bigdecimal::{BigDecimal, Zero};
+    use std::str::FromStr;
 
     #[test]
     fn test_calculate_ordinal_position_01() {
@@ -581,4 +1071,278 @@ mod tests {
             3
         );
     }
+
+    #[test]
+    fn test_transfer_to_op_return_burns() {
+        let txs = serde_json::from_str::<Transaction>(
+            r#"{"transaction": "send", "inputs": [["transfer:0", 1000]],
+                "outputs": [["send:0", 0], ["send:1", 1000]], "output_addresses": ["", "receiver"],
+                "inscriptions": []}"#,
+        )
+        .unwrap();
+        // Nobody is credited for the OP_RETURN output
+        assert_eq!(Indexer::spend_receiver(&txs, 0, "sender"), None);
+        assert_eq!(Indexer::spend_receiver(&txs, 1, "sender"), Some("receiver"));
+        // Spent as fee it returns to the sender
+        assert_eq!(Indexer::spend_receiver(&txs, 2, "sender"), Some("sender"));
+
+        let sender = |balance: &str, transfer_balance: &str| Balances {
+            id: 1,
+            address: "sender".to_string(),
+            ticker: "lite".to_string(),
+            balance: BigDecimal::from_str(balance).unwrap(),
+            transfer_balance: BigDecimal::from_str(transfer_balance).unwrap(),
+        };
+        let amount = BigDecimal::from(100);
+        // The sender's transfer balance is debited, and an emptied sender is no longer a holder
+        assert_eq!(
+            Indexer::burn_from(&sender("0", "100"), &amount),
+            (BigDecimal::from(0), -1)
+        );
+        assert_eq!(
+            Indexer::burn_from(&sender("5", "150"), &amount),
+            (BigDecimal::from(50), 0)
+        );
+    }
+
+    #[test]
+    fn test_mint_credit_final_mint() {
+        let supply = BigDecimal::from(1000);
+        // The final mint is credited the remaining supply and records what it requested
+        assert_eq!(
+            Indexer::mint_credit(&supply, &BigDecimal::from(950), &BigDecimal::from(100)),
+            (BigDecimal::from(50), true)
+        );
+        assert_eq!(
+            Indexer::mint_credit(&supply, &BigDecimal::from(900), &BigDecimal::from(100)),
+            (BigDecimal::from(100), false)
+        );
+        assert_eq!(
+            Indexer::mint_credit(
+                &supply,
+                &BigDecimal::from_str("999.5").unwrap(),
+                &BigDecimal::from(1)
+            ),
+            (BigDecimal::from_str("0.5").unwrap(), true)
+        );
+    }
+
+    #[test]
+    fn test_reinscription_by_charm() {
+        let charms = vec!["uncommon".to_string(), "reinscription".to_string()];
+        // The charm is enough, without looking up the sat
+        assert_eq!(
+            Indexer::invalid_reason(&charms, || panic!("sat looked up")).unwrap(),
+            Some("reinscription".to_string())
+        );
+        assert_eq!(
+            Indexer::invalid_reason(&charms[..1], || Ok(false)).unwrap(),
+            None
+        );
+        assert_eq!(
+            Indexer::invalid_reason(&[], || Ok(true)).unwrap(),
+            Some("reinscription".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reinscription_by_sat_and_satpoint() {
+        let stored = Inscriptions {
+            inscription_id: "firsti0".to_string(),
+            output: "first:0".to_string(),
+            output_offset: 0,
+            spent_output: Some("send:1".to_string()),
+            spent_offset: Some(200),
+            sat: Some(5000),
+            ..Default::default()
+        };
+        let satpoint = |output: &str, offset| (output.to_string(), offset);
+        // The same sat number
+        assert!(Indexer::inscribes_sat(&stored, Some(5000), None));
+        assert!(!Indexer::inscribes_sat(&stored, Some(5001), None));
+        // Where it was created, or where it was sent
+        assert!(Indexer::inscribes_sat(
+            &stored,
+            None,
+            Some(&satpoint("first:0", 0))
+        ));
+        assert!(Indexer::inscribes_sat(
+            &stored,
+            Some(5001),
+            Some(&satpoint("send:1", 200))
+        ));
+        // Another sat of the same output
+        assert!(!Indexer::inscribes_sat(
+            &stored,
+            None,
+            Some(&satpoint("send:1", 0))
+        ));
+        assert!(!Indexer::inscribes_sat(&stored, None, None));
+    }
+
+    #[test]
+    fn test_reinscribed_deploy_and_mint_are_skipped() {
+        for action in ["deploy", "mint"] {
+            let inscription = Inscriptions {
+                action: action.to_string(),
+                ticker: "lite".to_string(),
+                invalid_reason: Indexer::invalid_reason(&[], || Ok(true)).unwrap(),
+                ..Default::default()
+            };
+            assert_eq!(Indexer::skip_reason(&inscription), Some("reinscription"));
+        }
+        let first = Inscriptions {
+            action: "deploy".to_string(),
+            invalid_reason: Indexer::invalid_reason(&[], || Ok(false)).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(Indexer::skip_reason(&first), None);
+    }
+
+    fn tracker() -> Tracker {
+        Tracker {
+            ticker: "lite".to_string(),
+            deploy_inscription: "deployi0".to_string(),
+            supply: BigDecimal::from(1000),
+            supply_minted: BigDecimal::from(100),
+            limit_mint: BigDecimal::from(10),
+            decimals: 2,
+            ..Default::default()
+        }
+    }
+
+    fn operation(action: &str, amount: Option<&str>) -> Inscriptions {
+        Inscriptions {
+            action: action.to_string(),
+            ticker: "lite".to_string(),
+            original_ticker: "lite".to_string(),
+            amount: amount.map(|amount| BigDecimal::from_str(amount).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn balance(balance: i64) -> Balances {
+        Balances {
+            id: 1,
+            address: "alice".to_string(),
+            ticker: "lite".to_string(),
+            balance: BigDecimal::from(balance),
+            transfer_balance: BigDecimal::zero(),
+        }
+    }
+
+    #[test]
+    fn test_deploy_rejection() {
+        let protocol = Protocol::new("ltc-20");
+        let rules = protocol.rules_at(0);
+        let deploy = |ticker: &str, supply: Option<i64>| Inscriptions {
+            original_ticker: ticker.to_string(),
+            supply: supply.map(BigDecimal::from),
+            ..operation("deploy", None)
+        };
+
+        assert!(Indexer::deploy_rejection(rules, None, &deploy("lite", Some(1000))).is_ok());
+        assert_eq!(
+            Indexer::deploy_rejection(rules, Some(&tracker()), &deploy("lite", Some(1000))).err(),
+            Some("ticker already deployed")
+        );
+        assert_eq!(
+            Indexer::deploy_rejection(rules, None, &deploy("litecoin", Some(1000))).err(),
+            Some("invalid ticker")
+        );
+        assert_eq!(
+            Indexer::deploy_rejection(rules, None, &deploy("lite", None)).err(),
+            Some("invalid deploy values")
+        );
+    }
+
+    #[test]
+    fn test_mint_rejection_of_undeployed_ticker() {
+        assert_eq!(
+            Indexer::mint_rejection(None, &operation("mint", Some("10"))),
+            Some("ticker not deployed")
+        );
+    }
+
+    #[test]
+    fn test_mint_rejection() {
+        let ticker = tracker();
+        let reject = |amount| Indexer::mint_rejection(Some(&ticker), &operation("mint", amount));
+
+        assert_eq!(reject(Some("10")), None);
+        assert_eq!(reject(None), Some("invalid amount"));
+        assert_eq!(reject(Some("0")), Some("invalid amount"));
+        assert_eq!(reject(Some("0.001")), Some("too many decimals"));
+        assert_eq!(reject(Some("11")), Some("amount over the mint limit"));
+
+        let minted = Tracker {
+            supply_minted: BigDecimal::from(1000),
+            ..tracker()
+        };
+        assert_eq!(
+            Indexer::mint_rejection(Some(&minted), &operation("mint", Some("10"))),
+            Some("supply fully minted")
+        );
+
+        let self_minted = Tracker {
+            self_mint: true,
+            ..tracker()
+        };
+        let child = Inscriptions {
+            parent_id: Some("deployi0".to_string()),
+            ..operation("mint", Some("10"))
+        };
+        assert_eq!(Indexer::mint_rejection(Some(&self_minted), &child), None);
+        assert_eq!(
+            Indexer::mint_rejection(Some(&self_minted), &operation("mint", Some("10"))),
+            Some("not a child of the deploy inscription")
+        );
+    }
+
+    #[test]
+    fn test_transfer_rejection_of_undeployed_ticker() {
+        assert_eq!(
+            Indexer::transfer_rejection(None, None, &operation("transfer", Some("10"))),
+            Some("ticker not deployed")
+        );
+    }
+
+    #[test]
+    fn test_transfer_rejection_of_missing_amount() {
+        assert_eq!(
+            Indexer::transfer_rejection(
+                Some(&tracker()),
+                Some(&balance(50)),
+                &operation("transfer", None)
+            ),
+            Some("missing amount")
+        );
+    }
+
+    #[test]
+    fn test_transfer_rejection_without_balance() {
+        assert_eq!(
+            Indexer::transfer_rejection(Some(&tracker()), None, &operation("transfer", Some("10"))),
+            Some("insufficient balance")
+        );
+    }
+
+    #[test]
+    fn test_transfer_rejection_of_invalid_amount() {
+        let ticker = tracker();
+        let account = balance(50);
+        let reject = |amount| {
+            Indexer::transfer_rejection(
+                Some(&ticker),
+                Some(&account),
+                &operation("transfer", Some(amount)),
+            )
+        };
+
+        assert_eq!(reject("10"), None);
+        assert_eq!(reject("0"), Some("invalid amount"));
+        assert_eq!(reject("-5"), Some("invalid amount"));
+        assert_eq!(reject("0.001"), Some("too many decimals"));
+        assert_eq!(reject("51"), Some("insufficient balance"));
+    }
 }
@@ -1,16 +1,33 @@
 extern crate core;
 
+mod content;
 mod database;
+mod diff;
+mod events;
+mod export;
 mod indexer;
+mod mempool;
 mod models;
+mod ordering;
 mod ordinals;
+mod protocol;
+mod replay;
 mod schema;
+mod state_hash;
+mod stats;
 mod util;
+mod verify;
 
+use crate::content::ContentCache;
 use crate::database::Database;
+use crate::events::{EventSink, OutboxWorker};
 use crate::indexer::Indexer;
+use crate::mempool::MempoolWatcher;
 use crate::ordinals::Ordinals;
+use crate::protocol::{Protocol, TickerRule};
+use crate::util::canonical_ticker;
 use dotenv::dotenv;
+use std::collections::BTreeMap;
 use std::env;
 use std::process::exit;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -41,6 +58,56 @@ async fn main() {
         .unwrap_or_default()
         .parse::<i32>()
         .unwrap_or(0);
+    let verify_interval = env::var("VERIFY_INTERVAL")
+        .unwrap_or_default()
+        .parse::<i32>()
+        .unwrap_or(0);
+    let ticker_rules = TickerRule::parse_list(&env::var("TICKER_RULES").unwrap_or_default())
+        .expect(
+            "TICKER_RULES must be a ; separated list of <height>:<lengths>[/<self_mint lengths>]",
+        );
+    let mut protocol = Protocol::new(&meta_protocol).with_ticker_rules(&ticker_rules);
+    if let Ok(content_types) = env::var("CONTENT_TYPES") {
+        let content_types = content_types
+            .split(',')
+            .map(|content_type| content_type.trim().to_lowercase())
+            .filter(|content_type| !content_type.is_empty())
+            .collect::<Vec<_>>();
+        if !content_types.is_empty() {
+            protocol = protocol.with_content_types(&content_types);
+        }
+    }
+    if let Ok(strict_content_height) = env::var("STRICT_CONTENT_HEIGHT") {
+        if !strict_content_height.is_empty() {
+            protocol = protocol.with_strict_content_height(
+                strict_content_height
+                    .parse()
+                    .expect("STRICT_CONTENT_HEIGHT must be a block height"),
+            );
+        }
+    }
+    if let Ok(jubilee_height) = env::var("JUBILEE_HEIGHT") {
+        if !jubilee_height.is_empty() {
+            protocol = protocol.with_jubilee_height(
+                jubilee_height
+                    .parse()
+                    .expect("JUBILEE_HEIGHT must be a block height"),
+            );
+        }
+    }
+    let event_sinks = EventSink::parse_list(&env::var("EVENT_SINKS").unwrap_or_default())
+        .expect("EVENT_SINKS must be a comma separated list of stdout, file:<path>, webhook:<url> or notify:<channel>");
+
+    let content_cache = ContentCache::new(
+        env::var("CONTENT_CACHE_SIZE")
+            .unwrap_or_default()
+            .parse::<usize>()
+            .unwrap_or(1000),
+        env::var("MAX_CONTENT_SIZE")
+            .unwrap_or_default()
+            .parse::<usize>()
+            .unwrap_or(1024 * 1024),
+    );
 
     let database = Database::new().unwrap();
     let ordinals: Ordinals = Ordinals::new(ordinals_url);
@@ -48,9 +115,252 @@ async fn main() {
     let mut indexer = Indexer {
         ordinals,
         database,
-        meta_protocol,
+        protocol,
+        content_cache,
+        verify_interval,
+        event_sinks,
+        events: Vec::new(),
     };
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        match command.as_str() {
+            "verify" => {
+                let discrepancies = match indexer.verify(args.get(1).cloned()) {
+                    Ok(discrepancies) => discrepancies,
+                    Err(err) => {
+                        eprintln!("Verification failed: {}", err);
+                        exit(1);
+                    }
+                };
+                for discrepancy in &discrepancies {
+                    println!("{}", discrepancy);
+                }
+                log::info!("Verification found {} discrepancies", discrepancies.len());
+                exit(if discrepancies.is_empty() { 0 } else { 1 });
+            }
+            "replay" => match indexer.replay() {
+                Ok(_) => exit(0),
+                Err(err) => {
+                    eprintln!("Replay failed: {:?}", err);
+                    exit(1);
+                }
+            },
+            "reindex" => {
+                let Some(from_height) =
+                    flag_value(&args, "--from").and_then(|height| height.parse::<i64>().ok())
+                else {
+                    eprintln!("Usage: ord20-indexer reindex --from <height>");
+                    exit(2);
+                };
+                if let Err(err) = indexer.rollback(from_height) {
+                    eprintln!("Reindex failed: {:?}", err);
+                    exit(1);
+                }
+            }
+            "export" => {
+                let Some(ticker) = args.get(1).filter(|ticker| !ticker.starts_with("--")) else {
+                    eprintln!("Usage: ord20-indexer export <ticker> [--height <height>] [--format csv|json]");
+                    exit(2);
+                };
+                let height = flag_value(&args, "--height").and_then(|height| height.parse().ok());
+                let format = flag_value(&args, "--format").unwrap_or("csv");
+                match indexer.export_holders(
+                    canonical_ticker(ticker),
+                    height,
+                    format,
+                    &mut std::io::stdout().lock(),
+                ) {
+                    Ok(count) => {
+                        log::info!("Exported {} holders of {}", count, ticker);
+                        exit(0);
+                    }
+                    Err(err) => {
+                        eprintln!("Export failed: {}", err);
+                        exit(1);
+                    }
+                }
+            }
+            "balance" => {
+                let (Some(address), Some(ticker)) = (args.get(1), args.get(2)) else {
+                    eprintln!(
+                        "Usage: ord20-indexer balance <address> <ticker> [--height <height>]"
+                    );
+                    exit(2);
+                };
+                let height = flag_value(&args, "--height").and_then(|height| height.parse().ok());
+                match indexer.address_balance(address.to_string(), canonical_ticker(ticker), height)
+                {
+                    Ok(balance) => {
+                        println!("{}", serde_json::to_string(&balance).unwrap());
+                        exit(0);
+                    }
+                    Err(err) => {
+                        eprintln!("Query failed: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
+            "stats" => {
+                let Some(ticker) = args.get(1).filter(|ticker| !ticker.starts_with("--")) else {
+                    eprintln!("Usage: ord20-indexer stats <ticker> [--blocks <window>]");
+                    exit(2);
+                };
+                let blocks = flag_value(&args, "--blocks")
+                    .and_then(|blocks| blocks.parse::<i64>().ok())
+                    .filter(|blocks| *blocks > 0)
+                    .unwrap_or(100);
+                match indexer.mint_stats(
+                    canonical_ticker(ticker),
+                    blocks,
+                    &mut std::io::stdout().lock(),
+                ) {
+                    Ok(_) => exit(0),
+                    Err(err) => {
+                        eprintln!("Stats failed: {}", err);
+                        exit(1);
+                    }
+                }
+            }
+            "transferable" => {
+                let Some(address) = args.get(1) else {
+                    eprintln!("Usage: ord20-indexer transferable <address> [ticker]");
+                    exit(2);
+                };
+                match indexer.database.get_transferable_inscriptions(
+                    address.to_string(),
+                    args.get(2).map(|ticker| canonical_ticker(ticker)),
+                ) {
+                    Ok(inscriptions) => {
+                        for inscription in inscriptions {
+                            println!(
+                                "{}",
+                                serde_json::json!({
+                                    "inscription_id": inscription.inscription_id,
+                                    "inscription_num": inscription.inscription_num,
+                                    "ticker": inscription.ticker,
+                                    "amount": inscription.amount,
+                                    "output": inscription.output,
+                                    "offset": inscription.output_offset,
+                                })
+                            );
+                        }
+                        exit(0);
+                    }
+                    Err(err) => {
+                        eprintln!("Query failed: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
+            "hash" => {
+                let Some(height) = args.get(1).and_then(|height| height.parse::<i64>().ok()) else {
+                    eprintln!("Usage: ord20-indexer hash <height>");
+                    exit(2);
+                };
+                match indexer.database.get_block(height) {
+                    Ok(block) => {
+                        println!(
+                            "{} {} {} {} {}",
+                            block.height,
+                            block.block_hash,
+                            block.event_count,
+                            block.event_hash,
+                            block.cumulative_hash
+                        );
+                        exit(0);
+                    }
+                    Err(err) => {
+                        eprintln!("No state hash for block {}: {:?}", height, err);
+                        exit(1);
+                    }
+                }
+            }
+            "dead-letters" => {
+                let from_height = flag_value(&args, "--from")
+                    .and_then(|height| height.parse::<i64>().ok())
+                    .unwrap_or(0);
+                match indexer.database.get_dead_letters(from_height) {
+                    Ok(dead_letters) => {
+                        for dead_letter in dead_letters {
+                            println!(
+                                "{}",
+                                serde_json::json!({
+                                    "height": dead_letter.height,
+                                    "tx_id": dead_letter.tx_id,
+                                    "inscription_id": dead_letter.inscription_id,
+                                    "error": dead_letter.error,
+                                    "payload": dead_letter.payload,
+                                })
+                            );
+                        }
+                        exit(0);
+                    }
+                    Err(err) => {
+                        eprintln!("Query failed: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
+            "diff" => {
+                let Some(content) = args
+                    .get(1)
+                    .and_then(|path| std::fs::read_to_string(path).ok())
+                else {
+                    eprintln!("Usage: ord20-indexer diff <reference.json|reference.csv>");
+                    exit(2);
+                };
+                match indexer.diff(&content) {
+                    Ok(count) => {
+                        log::info!("Found {} differences", count);
+                        exit(if count == 0 { 0 } else { 1 });
+                    }
+                    Err(err) => {
+                        eprintln!("Diff failed: {}", err);
+                        exit(1);
+                    }
+                }
+            }
+            _ => {
+                eprintln!(
+                    "Unknown command '{}'. Available commands: verify, replay, reindex, export, balance, stats, transferable, hash, dead-letters, diff",
+                    command
+                );
+                exit(2);
+            }
+        }
+    }
+
+    if !indexer.event_sinks.is_empty() {
+        let worker = OutboxWorker {
+            database: Database::new().unwrap(),
+            event_sinks: indexer.event_sinks.clone(),
+        };
+        tokio::spawn(worker.run());
+    }
+
+    if let Ok(mempool_url) = env::var("MEMPOOL_URL") {
+        if !mempool_url.is_empty() {
+            let watcher = MempoolWatcher {
+                ordinals: indexer.ordinals.clone(),
+                database: Database::new().unwrap(),
+                protocol: indexer.protocol.clone(),
+                content_cache: ContentCache::new(
+                    indexer.content_cache.capacity,
+                    indexer.content_cache.max_size,
+                ),
+                mempool_url,
+                interval: env::var("MEMPOOL_INTERVAL")
+                    .unwrap_or_default()
+                    .parse::<u64>()
+                    .unwrap_or(10),
+                blocks_behind,
+                blocks: BTreeMap::new(),
+            };
+            tokio::spawn(watcher.run());
+        }
+    }
+
     loop {
         let last_height = indexer
             .database
@@ -75,3 +385,10 @@ async fn main() {
         sleep(Duration::from_secs(5));
     }
 }
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
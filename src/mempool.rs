@@ -0,0 +1,494 @@
+use crate::content::{fetch_content, inscription_body, parse_operation, ContentCache};
+use crate::database::Database;
+use crate::indexer::Indexer;
+use crate::models::{Inscriptions, PendingBalancesInsert, Tracker};
+use crate::ordinals::{Block, Ordinals, Transaction};
+use crate::protocol::Protocol;
+use crate::util::{bigdecimal_fractional_count, canonical_ticker, parse_satpoint};
+use crate::SHUTTING_DOWN;
+use bigdecimal::{BigDecimal, Zero};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Computes the provisional balance changes of transactions that have not been indexed yet:
+/// valid mints credit the minter, valid transfer inscriptions move their amount from the
+/// inscriber's balance to their transfer balance, and spent transfer inscriptions move it from
+/// the sender's transfer balance to the receiver's balance. Transfer inscriptions inscribed by
+/// earlier `transactions` can be spent by later ones. `get_transfers` returns the unspent
+/// transfer inscriptions on an output in the order of their sats, and `get_balance` an
+/// address's indexed balance of a ticker.
+pub fn pending_changes(
+    transactions: &[Transaction],
+    meta_protocol: &str,
+    mut get_tracker: impl FnMut(&str) -> Option<Tracker>,
+    mut get_transfers: impl FnMut(&str) -> Vec<Inscriptions>,
+    mut get_balance: impl FnMut(&str, &str) -> Option<BigDecimal>,
+) -> Vec<PendingBalancesInsert> {
+    let mut changes: Vec<PendingBalancesInsert> = Vec::new();
+    let mut pending_minted: HashMap<String, BigDecimal> = HashMap::new();
+    // Pending transfer inscriptions by the output they were inscribed on
+    let mut pending_transfers: HashMap<String, Vec<Inscriptions>> = HashMap::new();
+
+    for txs in transactions {
+        let mut input_offset = 0;
+        for (index, (input, input_value)) in txs.inputs.iter().enumerate() {
+            let mut transfers = get_transfers(input);
+            transfers.extend(pending_transfers.remove(input).unwrap_or_default());
+            transfers.sort_by_key(|inscription| inscription.output_offset);
+            for inscription in transfers {
+                let amount = inscription.amount.clone().unwrap_or_default();
+                let vout = Indexer::calculate_ordinal_position(
+                    index,
+                    input_offset + inscription.output_offset as u64,
+                    &txs.outputs,
+                );
+                // Spent as fee returns to the sender, outputs without an address burn it
+                let address_receiver = if vout < txs.outputs.len() {
+                    txs.output_addresses
+                        .get(vout)
+                        .filter(|address| !address.is_empty())
+                } else {
+                    Some(&inscription.genesis_address)
+                };
+
+                changes.push(PendingBalancesInsert {
+                    address: inscription.genesis_address.clone(),
+                    ticker: inscription.ticker.clone(),
+                    balance: BigDecimal::zero(),
+                    transfer_balance: -amount.clone(),
+                    tx_id: txs.transaction.clone(),
+                });
+                if let Some(address_receiver) = address_receiver {
+                    changes.push(PendingBalancesInsert {
+                        address: address_receiver.clone(),
+                        ticker: inscription.ticker.clone(),
+                        balance: amount,
+                        transfer_balance: BigDecimal::zero(),
+                        tx_id: txs.transaction.clone(),
+                    });
+                }
+            }
+            input_offset += input_value;
+        }
+
+        let Some(first_address) = txs.output_addresses.first() else {
+            continue;
+        };
+        for tx_inscription in &txs.inscriptions {
+            // Without a satpoint, assume inscription N of the reveal is on output N
+            let (output, output_offset) = tx_inscription
+                .satpoint
+                .as_deref()
+                .and_then(parse_satpoint)
+                .unwrap_or((tx_inscription.inscription_id.replace('i', ":"), 0));
+            let address = txs
+                .outputs
+                .iter()
+                .position(|(outpoint, _)| *outpoint == output)
+                .and_then(|vout| txs.output_addresses.get(vout))
+                .filter(|address| !address.is_empty())
+                .unwrap_or(first_address);
+            let Some(ord20) = inscription_body(
+                tx_inscription.content.as_deref(),
+                tx_inscription.content_encoding.as_deref(),
+            )
+            .ok()
+            .and_then(|content| {
+                parse_operation(&String::from_utf8_lossy(&content), meta_protocol)
+                    .ok()
+                    .flatten()
+            }) else {
+                continue;
+            };
+            if ord20.op != "mint" && ord20.op != "transfer" {
+                continue;
+            }
+
+            let ticker = canonical_ticker(&ord20.tick);
+            let Some(tracker) = get_tracker(&ticker) else {
+                continue;
+            };
+            let amount = ord20.amt.unwrap_or_default();
+            if amount <= BigDecimal::zero()
+                || bigdecimal_fractional_count(amount.clone()) > tracker.decimals as u32
+            {
+                continue;
+            }
+
+            if ord20.op == "transfer" {
+                let available = get_balance(address, &ticker).unwrap_or_default()
+                    + changes
+                        .iter()
+                        .filter(|change| change.address == *address && change.ticker == ticker)
+                        .map(|change| change.balance.clone())
+                        .sum::<BigDecimal>();
+                if available < amount {
+                    continue;
+                }
+                changes.push(PendingBalancesInsert {
+                    address: address.clone(),
+                    ticker: ticker.clone(),
+                    balance: -amount.clone(),
+                    transfer_balance: amount.clone(),
+                    tx_id: txs.transaction.clone(),
+                });
+                pending_transfers
+                    .entry(output)
+                    .or_default()
+                    .push(Inscriptions {
+                        inscription_id: tx_inscription.inscription_id.clone(),
+                        genesis_address: address.clone(),
+                        ticker,
+                        action: "transfer".to_string(),
+                        amount: Some(amount),
+                        valid: Some(true),
+                        spent: Some(false),
+                        output_offset: output_offset as i64,
+                        ..Default::default()
+                    });
+                continue;
+            }
+
+            if tracker.limit_mint < amount
+                || (tracker.self_mint
+                    && tx_inscription.parent.as_ref() != Some(&tracker.deploy_inscription))
+            {
+                continue;
+            }
+
+            let minted = pending_minted
+                .entry(ticker.clone())
+                .or_insert(tracker.supply_minted.clone());
+            let credited = amount.min(tracker.supply.clone() - minted.clone());
+            if credited <= BigDecimal::zero() {
+                continue;
+            }
+            *minted += credited.clone();
+
+            changes.push(PendingBalancesInsert {
+                address: address.clone(),
+                ticker,
+                balance: credited,
+                transfer_balance: BigDecimal::zero(),
+                tx_id: txs.transaction.clone(),
+            });
+        }
+    }
+    changes
+}
+
+/// Maintains the provisional `pending_balances` layer on its own database connection.
+///
+/// Every refresh recomputes the layer from the blocks between `last_height` and the chain tip
+/// (which the indexer has not reached yet because of `BLOCKS_BEHIND`) and the current mempool.
+/// Those blocks are cached in `blocks`, so each is fetched once.
+/// Changes are promoted implicitly once the indexer processes their block, and mempool
+/// transactions that are dropped disappear on the next refresh.
+pub struct MempoolWatcher {
+    pub ordinals: Ordinals,
+    pub database: Database,
+    pub protocol: Protocol,
+    pub content_cache: ContentCache,
+    pub mempool_url: String,
+    pub interval: u64,
+    pub blocks_behind: i32,
+    /// The prepared blocks past `last_height` by height.
+    pub blocks: BTreeMap<i32, Block>,
+}
+
+impl MempoolWatcher {
+    pub async fn run(mut self) {
+        while !SHUTTING_DOWN.load(Ordering::Relaxed) {
+            match self.refresh().await {
+                Ok(count) => log::debug!("Pending balance changes: {}", count),
+                Err(err) => log::error!("Failed to refresh pending balances: {}", err),
+            }
+            tokio::time::sleep(Duration::from_secs(self.interval)).await;
+        }
+    }
+
+    async fn refresh(&mut self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let last_height = self
+            .database
+            .get_status("last_height".to_string())?
+            .value
+            .unwrap_or_default()
+            .parse::<i32>()?;
+        let current_height = self
+            .ordinals
+            .get_block_height()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        // The indexer keeps `BLOCKS_BEHIND` blocks behind the tip, or one more until it processes
+        // a new block. Further behind it is catching up, and pending changes would be incomplete.
+        if current_height - last_height > self.blocks_behind + 1 {
+            log::debug!(
+                "Indexer is {} blocks behind, clearing pending balances",
+                current_height - last_height
+            );
+            self.blocks.clear();
+            return Ok(self.database.replace_pending_balances(&[])?);
+        }
+
+        let mut transactions = self.pending_blocks(last_height + 1, current_height).await?;
+        let mut mempool = self.ordinals.get_mempool(&self.mempool_url).await?;
+        self.prepare_transactions(&mut mempool, current_height as i64 + 1)
+            .await?;
+        transactions.extend(mempool);
+
+        let database = RefCell::new(&mut self.database);
+        let changes = pending_changes(
+            &transactions,
+            &self.protocol.meta_protocol,
+            |ticker| database.borrow_mut().get_tracker(ticker.to_string()).ok(),
+            |output| {
+                database
+                    .borrow_mut()
+                    .get_inscription_by_output(output.to_string())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|inscription| {
+                        inscription.action == "transfer"
+                            && inscription.valid.unwrap_or_default()
+                            && !inscription.spent.unwrap_or_default()
+                    })
+                    .collect()
+            },
+            |address, ticker| {
+                database
+                    .borrow_mut()
+                    .get_balance(address.to_string(), ticker.to_string())
+                    .ok()
+                    .map(|balance| balance.balance)
+            },
+        );
+        Ok(self.database.replace_pending_balances(&changes)?)
+    }
+
+    /// The prepared transactions of the blocks from `from` to `to`. Blocks are kept in `blocks`
+    /// between refreshes, and all are fetched again when a block does not build on the one
+    /// before it.
+    async fn pending_blocks(
+        &mut self,
+        from: i32,
+        to: i32,
+    ) -> Result<Vec<Transaction>, Box<dyn std::error::Error + Send + Sync>> {
+        self.blocks.retain(|height, _| (from..=to).contains(height));
+        let mut refetched = false;
+        'fetch: loop {
+            let mut transactions = Vec::new();
+            let mut previous_hash: Option<String> = None;
+            for height in from..=to {
+                let block = match self.blocks.remove(&height) {
+                    Some(block) => block,
+                    None => {
+                        let mut block = self.ordinals.get_block(height).await?;
+                        self.prepare_transactions(&mut block.transactions, height as i64)
+                            .await?;
+                        block
+                    }
+                };
+                if previous_hash.is_some_and(|hash| hash != block.previous_blockhash) && !refetched
+                {
+                    log::debug!(
+                        "Block {} was reorganised, fetching pending blocks",
+                        height - 1
+                    );
+                    self.blocks.clear();
+                    refetched = true;
+                    continue 'fetch;
+                }
+                transactions.extend(block.transactions.iter().cloned());
+                previous_hash = Some(block.hash.clone());
+                self.blocks.insert(height, block);
+            }
+            return Ok(transactions);
+        }
+    }
+
+    /// Drops the reveals of `transactions` that the indexer would not accept at `height` and
+    /// fetches the content that block data leaves out.
+    async fn prepare_transactions(
+        &mut self,
+        transactions: &mut [Transaction],
+        height: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let rules = self.protocol.rules_at(height);
+        for txs in transactions {
+            let mut inscriptions = Vec::new();
+            for mut tx_inscription in std::mem::take(&mut txs.inscriptions) {
+                if !rules.accepts_reveal(
+                    tx_inscription.inscription_number,
+                    tx_inscription.decoded_content_type().as_deref(),
+                ) {
+                    continue;
+                }
+                let sat = tx_inscription.sat.map(|sat| sat as i64);
+                let satpoint = tx_inscription
+                    .satpoint
+                    .as_deref()
+                    .and_then(parse_satpoint)
+                    .map(|(output, offset)| (output, offset as i64));
+                let invalid_reason = Indexer::invalid_reason(&tx_inscription.charms, || {
+                    Indexer::inscribed_before(
+                        &mut self.database,
+                        &tx_inscription.inscription_id,
+                        sat,
+                        satpoint.as_ref(),
+                    )
+                })?;
+                if invalid_reason.is_some() {
+                    continue;
+                }
+
+                if tx_inscription.content.is_none() {
+                    if let Ok((body, content_encoding)) = fetch_content(
+                        &mut self.ordinals,
+                        &mut self.content_cache,
+                        &tx_inscription.inscription_id,
+                    )
+                    .await
+                    {
+                        tx_inscription.content = Some(hex::encode(body));
+                        tx_inscription.content_encoding = content_encoding.map(hex::encode);
+                    }
+                }
+                if !rules.strict_content {
+                    tx_inscription.content_encoding = None;
+                }
+                inscriptions.push(tx_inscription);
+            }
+            txs.inscriptions = inscriptions;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mempool::pending_changes;
+    use crate::models::{Inscriptions, Tracker};
+    use crate::ordinals::Transaction;
+    use bigdecimal::{BigDecimal, Zero};
+
+    fn tracker() -> Tracker {
+        Tracker {
+            ticker: "lite".to_string(),
+            supply: BigDecimal::from(1000),
+            supply_minted: BigDecimal::from(950),
+            limit_mint: BigDecimal::from(100),
+            decimals: 18,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pending_changes() {
+        let mint = hex::encode(r#"{"p":"ltc-20","op":"mint","tick":"LITE","amt":"100"}"#);
+        let transactions = serde_json::from_str::<Vec<Transaction>>(&format!(
+            r#"[
+                {{"transaction":"mint","inputs":[["funding:0",1000]],"outputs":[["mint:0",546]],"output_addresses":["minter"],
+                  "inscriptions":[{{"inscription_id":"minti0","inscription_number":10,"content_type":null,"content":"{}"}}]}},
+                {{"transaction":"send","inputs":[["fee:0",1000],["transfer:0",546]],"outputs":[["send:0",1000],["send:1",100],["send:2",446]],
+                  "output_addresses":["change","receiver","other"],"inscriptions":[]}},
+                {{"transaction":"burn","inputs":[["burnable:0",546]],"outputs":[["burn:0",546]],
+                  "output_addresses":[""],"inscriptions":[]}}
+            ]"#,
+            mint
+        ))
+        .unwrap();
+
+        let changes = pending_changes(
+            &transactions,
+            "ltc-20",
+            |ticker| (ticker == "lite").then(tracker),
+            |output| {
+                let transfer = |amount, output_offset| Inscriptions {
+                    genesis_address: "sender".to_string(),
+                    ticker: "lite".to_string(),
+                    action: "transfer".to_string(),
+                    amount: Some(BigDecimal::from(amount)),
+                    valid: Some(true),
+                    spent: Some(false),
+                    output_offset,
+                    ..Default::default()
+                };
+                match output {
+                    "transfer:0" => vec![transfer(25, 0), transfer(5, 200)],
+                    "burnable:0" => vec![transfer(7, 0)],
+                    _ => vec![],
+                }
+            },
+            |_, _| None,
+        );
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (
+                    change.address.as_str(),
+                    change.balance.clone(),
+                    change.transfer_balance.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("minter", BigDecimal::from(50), BigDecimal::zero()),
+                ("sender", BigDecimal::zero(), BigDecimal::from(-25)),
+                ("receiver", BigDecimal::from(25), BigDecimal::zero()),
+                ("sender", BigDecimal::zero(), BigDecimal::from(-5)),
+                ("other", BigDecimal::from(5), BigDecimal::zero()),
+                ("sender", BigDecimal::zero(), BigDecimal::from(-7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_changes_spend_pending_transfer() {
+        let transfer = |amount| {
+            hex::encode(format!(
+                r#"{{"p":"ltc-20","op":"transfer","tick":"lite","amt":"{}"}}"#,
+                amount
+            ))
+        };
+        let transactions = serde_json::from_str::<Vec<Transaction>>(&format!(
+            r#"[
+                {{"transaction":"inscribe","inputs":[["funding:0",1000]],"outputs":[["inscribe:0",546],["inscribe:1",454]],"output_addresses":["holder","holder"],
+                  "inscriptions":[{{"inscription_id":"inscribei0","inscription_number":10,"content_type":null,"content":"{}","satpoint":"inscribe:1:0"}},
+                                  {{"inscription_id":"inscribei1","inscription_number":11,"content_type":null,"content":"{}"}}]}},
+                {{"transaction":"send","inputs":[["inscribe:1",454]],"outputs":[["send:0",454]],
+                  "output_addresses":["receiver"],"inscriptions":[]}}
+            ]"#,
+            transfer(30),
+            transfer(50)
+        ))
+        .unwrap();
+
+        let changes = pending_changes(
+            &transactions,
+            "ltc-20",
+            |ticker| (ticker == "lite").then(tracker),
+            |_| vec![],
+            |address, _| (address == "holder").then(|| BigDecimal::from(60)),
+        );
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (
+                    change.address.as_str(),
+                    change.balance.clone(),
+                    change.transfer_balance.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("holder", BigDecimal::from(-30), BigDecimal::from(30)),
+                // Only 30 of the indexed 60 is left for the second transfer
+                ("holder", BigDecimal::zero(), BigDecimal::from(-30)),
+                ("receiver", BigDecimal::from(30), BigDecimal::zero()),
+            ]
+        );
+    }
+}
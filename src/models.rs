@@ -1,7 +1,8 @@
 use bigdecimal::BigDecimal;
 use diesel::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 
+#[allow(dead_code)]
 #[derive(Deserialize, Clone)]
 pub struct Ord20 {
     pub p: String,
@@ -10,7 +11,30 @@ pub struct Ord20 {
     pub max: Option<BigDecimal>,
     pub lim: Option<BigDecimal>,
     pub amt: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "deserialize_decimals")]
     pub dec: Option<i32>,
+    pub self_mint: Option<String>,
+}
+
+/// Reads `dec` from a string such as `"18"` like the other numeric fields, or from a number.
+fn deserialize_decimals<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<i32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Decimals {
+        Text(String),
+        Number(i32),
+    }
+    match Option::<Decimals>::deserialize(deserializer)? {
+        Some(Decimals::Text(dec)) => dec
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("invalid dec '{}'", dec))),
+        Some(Decimals::Number(dec)) => Ok(Some(dec)),
+        None => Ok(None),
+    }
 }
 
 #[allow(dead_code)]
@@ -23,6 +47,14 @@ pub struct Status {
     pub value: Option<String>,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::status)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StatusInsert {
+    pub key: String,
+    pub value: Option<String>,
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::inscriptions)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -41,6 +73,14 @@ pub struct InscriptionsInsert {
     pub value: i64,
     pub height: i64,
     pub timestamp: i64,
+    pub tx_index: i64,
+    pub original_ticker: String,
+    pub parent_id: Option<String>,
+    pub self_mint: bool,
+    pub output_offset: i64,
+    pub tx_offset: i64,
+    pub sat: Option<i64>,
+    pub invalid_reason: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -62,13 +102,15 @@ pub struct TrackerInsert {
     pub deploy_inscription_num: i64,
     pub deploy_inscription: String,
     pub ticker: String,
+    pub display_ticker: String,
     pub supply: BigDecimal,
     pub limit_mint: BigDecimal,
     pub decimals: i32,
+    pub self_mint: bool,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Queryable, Selectable)]
+#[derive(Debug, Clone, Default, Queryable, Selectable)]
 #[diesel(table_name = crate::schema::inscriptions)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Inscriptions {
@@ -95,10 +137,22 @@ pub struct Inscriptions {
     pub spent_offset: Option<i64>,
     pub spent_height: Option<i64>,
     pub spent_timestamp: Option<i64>,
+    pub tx_index: Option<i64>,
+    pub spent_tx_index: Option<i64>,
+    pub original_ticker: String,
+    pub parent_id: Option<String>,
+    pub self_mint: bool,
+    pub output_offset: i64,
+    pub spent_output: Option<String>,
+    pub tx_offset: Option<i64>,
+    pub spent_tx_offset: Option<i64>,
+    pub sat: Option<i64>,
+    pub invalid_reason: Option<String>,
+    pub burned: bool,
 }
 
 #[allow(dead_code)]
-#[derive(Queryable, Selectable)]
+#[derive(Default, Queryable, Selectable)]
 #[diesel(table_name = crate::schema::tracker)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Tracker {
@@ -114,6 +168,28 @@ pub struct Tracker {
     pub transactions: i64,
     pub inscription_mint_start: Option<i64>,
     pub inscription_mint_end: Option<i64>,
+    pub display_ticker: String,
+    pub self_mint: bool,
+    pub valid_mints: i64,
+    pub invalid_mints: i64,
+    pub unique_minters: i64,
+    pub first_mint_height: Option<i64>,
+    pub first_mint_timestamp: Option<i64>,
+    pub last_mint_height: Option<i64>,
+    pub last_mint_timestamp: Option<i64>,
+    pub burned: BigDecimal,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = crate::schema::tracker)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TrackerMintStats {
+    pub valid_mints: i64,
+    pub unique_minters: i64,
+    pub first_mint_height: Option<i64>,
+    pub first_mint_timestamp: Option<i64>,
+    pub last_mint_height: Option<i64>,
+    pub last_mint_timestamp: Option<i64>,
 }
 
 #[derive(Insertable)]
@@ -136,6 +212,30 @@ pub struct Balances {
     pub transfer_balance: BigDecimal,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::balance_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BalanceHistoryInsert {
+    pub address: String,
+    pub ticker: String,
+    pub balance: BigDecimal,
+    pub transfer_balance: BigDecimal,
+    pub height: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::balance_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BalanceHistory {
+    pub id: i32,
+    pub address: String,
+    pub ticker: String,
+    pub balance: BigDecimal,
+    pub transfer_balance: BigDecimal,
+    pub height: i64,
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::history)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -151,6 +251,8 @@ pub struct HistoryInsert {
     pub tx_id: String,
     pub height: i64,
     pub timestamp: i64,
+    pub amount_requested: BigDecimal,
+    pub partial_mint: bool,
 }
 
 #[allow(dead_code)]
@@ -170,4 +272,110 @@ pub struct History {
     pub tx_id: String,
     pub height: i64,
     pub timestamp: i64,
+    pub amount_requested: BigDecimal,
+    pub partial_mint: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::dead_letters)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DeadLettersInsert {
+    pub height: i64,
+    pub tx_id: String,
+    pub inscription_id: String,
+    pub payload: String,
+    pub error: String,
+}
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::dead_letters)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DeadLetters {
+    pub id: i32,
+    pub height: i64,
+    pub tx_id: String,
+    pub inscription_id: String,
+    pub payload: String,
+    pub error: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::mint_activity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MintActivityInsert {
+    pub ticker: String,
+    pub height: i64,
+    pub timestamp: i64,
+    pub mints: i64,
+    pub amount: BigDecimal,
+}
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::mint_activity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MintActivity {
+    pub id: i32,
+    pub ticker: String,
+    pub height: i64,
+    pub timestamp: i64,
+    pub mints: i64,
+    pub amount: BigDecimal,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::outbox)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OutboxInsert {
+    pub idempotency_key: String,
+    pub height: i64,
+    pub payload: String,
+}
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::outbox)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Outbox {
+    pub id: i64,
+    pub idempotency_key: String,
+    pub height: i64,
+    pub payload: String,
+    pub delivered: bool,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::pending_balances)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PendingBalancesInsert {
+    pub address: String,
+    pub ticker: String,
+    pub balance: BigDecimal,
+    pub transfer_balance: BigDecimal,
+    pub tx_id: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::blocks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BlocksInsert {
+    pub height: i64,
+    pub block_hash: String,
+    pub event_count: i32,
+    pub event_hash: String,
+    pub cumulative_hash: String,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::blocks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Blocks {
+    pub height: i64,
+    pub block_hash: String,
+    pub event_count: i32,
+    pub event_hash: String,
+    pub cumulative_hash: String,
 }
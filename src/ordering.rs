@@ -0,0 +1,216 @@
+use crate::indexer::Indexer;
+use crate::models::Inscriptions;
+use crate::ordinals::{Transaction, TransactionInscription};
+use crate::util::parse_satpoint;
+
+#[derive(Debug)]
+pub enum TxEvent<'a> {
+    /// A transfer inscription spent by the transaction, landing on `vout` (`outputs.len()`
+    /// when it is spent as fee).
+    Spend {
+        inscription: Box<Inscriptions>,
+        vout: usize,
+        output_offset: u64,
+    },
+    /// An inscription revealed by the transaction, created on `output` at `output_offset`.
+    Reveal {
+        inscription: &'a TransactionInscription,
+        output: String,
+        output_offset: u64,
+    },
+}
+
+/// An event and the offset of its sat among the transaction's outputs.
+#[derive(Debug)]
+pub struct OrderedEvent<'a> {
+    pub tx_offset: u64,
+    pub event: TxEvent<'a>,
+}
+
+impl OrderedEvent<'_> {
+    fn inscription_num(&self) -> i64 {
+        match &self.event {
+            TxEvent::Spend { inscription, .. } => inscription.inscription_num,
+            TxEvent::Reveal { inscription, .. } => inscription.inscription_number,
+        }
+    }
+}
+
+/// The offset among `outputs` of the sat at `output_offset` in output `vout`.
+fn tx_offset(vout: usize, output_offset: u64, outputs: &[(String, u64)]) -> u64 {
+    outputs
+        .iter()
+        .take(vout)
+        .map(|(_, value)| value)
+        .sum::<u64>()
+        + output_offset
+}
+
+/// Orders a transaction's spent transfer inscriptions and revealed inscriptions the way the
+/// protocol applies them: by where their sat ends up among the transaction's outputs, then by
+/// inscription number. `spends` holds each spent inscription with the index of its input and
+/// the total value of the inputs before it.
+pub fn transaction_events(
+    txs: &Transaction,
+    spends: Vec<(usize, u64, Inscriptions)>,
+) -> Vec<OrderedEvent<'_>> {
+    let mut events = Vec::new();
+    for (input_index, input_offset, inscription) in spends {
+        let ordinal_offset = input_offset + inscription.output_offset as u64;
+        let vout = Indexer::calculate_ordinal_position(input_index, ordinal_offset, &txs.outputs);
+        events.push(OrderedEvent {
+            tx_offset: ordinal_offset,
+            event: TxEvent::Spend {
+                output_offset: Indexer::calculate_output_offset(vout, ordinal_offset, &txs.outputs),
+                inscription: Box::new(inscription),
+                vout,
+            },
+        });
+    }
+    for inscription in &txs.inscriptions {
+        // Without a satpoint, assume inscription N of the reveal is on output N
+        let (output, output_offset) = inscription
+            .satpoint
+            .as_deref()
+            .and_then(parse_satpoint)
+            .unwrap_or((inscription.inscription_id.replace('i', ":"), 0));
+        let vout = txs
+            .outputs
+            .iter()
+            .position(|(outpoint, _)| *outpoint == output)
+            .unwrap_or(txs.outputs.len());
+        events.push(OrderedEvent {
+            tx_offset: tx_offset(vout, output_offset, &txs.outputs),
+            event: TxEvent::Reveal {
+                inscription,
+                output,
+                output_offset,
+            },
+        });
+    }
+    events.sort_by_key(|event| (event.tx_offset, event.inscription_num()));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::Inscriptions;
+    use crate::ordering::{transaction_events, TxEvent};
+    use crate::ordinals::Block;
+
+    /// Walks a block the way `process_block` does, with `stored` standing in for the
+    /// inscriptions table, and returns the applied events as `(kind, inscription, output)`.
+    fn apply_block(
+        block: &Block,
+        stored: &mut Vec<Inscriptions>,
+    ) -> Vec<(&'static str, String, String)> {
+        let mut applied = Vec::new();
+        for txs in &block.transactions {
+            let mut spends = Vec::new();
+            let mut input_offset = 0;
+            for (index, (input, value)) in txs.inputs.iter().enumerate() {
+                for inscription in stored
+                    .iter()
+                    .filter(|i| &i.output == input && !i.spent.unwrap_or_default())
+                {
+                    spends.push((index, input_offset, inscription.clone()));
+                }
+                input_offset += value;
+            }
+            for event in transaction_events(txs, spends) {
+                match event.event {
+                    TxEvent::Spend {
+                        inscription,
+                        vout,
+                        output_offset,
+                    } => {
+                        let stored = stored
+                            .iter_mut()
+                            .find(|i| i.inscription_id == inscription.inscription_id)
+                            .unwrap();
+                        stored.spent = Some(true);
+                        let output = txs
+                            .outputs
+                            .get(vout)
+                            .map(|(output, _)| output.clone())
+                            .unwrap_or_default();
+                        applied.push((
+                            "spend",
+                            inscription.inscription_id,
+                            format!("{}:{}", output, output_offset),
+                        ));
+                    }
+                    TxEvent::Reveal {
+                        inscription,
+                        output,
+                        output_offset,
+                    } => {
+                        stored.push(Inscriptions {
+                            inscription_id: inscription.inscription_id.clone(),
+                            inscription_num: inscription.inscription_number,
+                            output: output.clone(),
+                            output_offset: output_offset as i64,
+                            spent: Some(false),
+                            ..Default::default()
+                        });
+                        applied.push((
+                            "reveal",
+                            inscription.inscription_id.clone(),
+                            format!("{}:{}", output, output_offset),
+                        ));
+                    }
+                }
+            }
+        }
+        applied
+    }
+
+    const SAME_BLOCK_FIXTURE: &str = r#"{
+        "hash": "block", "height": 100, "previous_blockhash": "parent", "size": 0, "target": "", "weight": 0,
+        "timestamp": "2024-01-01 00:00:00 UTC",
+        "transactions": [
+            {"transaction": "reveal", "inputs": [["commit:0", 2000]],
+             "outputs": [["reveal:0", 1000], ["reveal:1", 1000]], "output_addresses": ["deployer", "sender"],
+             "inscriptions": [
+                {"inscription_id": "reveali0", "inscription_number": 7, "content_type": null, "content": null, "satpoint": "reveal:1:0"},
+                {"inscription_id": "reveali1", "inscription_number": 8, "content_type": null, "content": null, "satpoint": "reveal:0:0"}
+             ]},
+            {"transaction": "send", "inputs": [["fee:0", 500], ["reveal:1", 1000]],
+             "outputs": [["send:0", 500], ["send:1", 1000]], "output_addresses": ["change", "receiver"],
+             "inscriptions": [
+                {"inscription_id": "sendi0", "inscription_number": 9, "content_type": null, "content": null}
+             ]},
+            {"transaction": "batch", "inputs": [["send:0", 500]],
+             "outputs": [["batch:0", 300], ["batch:1", 200]], "output_addresses": ["receiver", "other"],
+             "inscriptions": [
+                {"inscription_id": "batchi0", "inscription_number": 11, "content_type": null, "content": null, "satpoint": "batch:1:0"},
+                {"inscription_id": "batchi1", "inscription_number": 10, "content_type": null, "content": null, "satpoint": "batch:0:0"}
+             ]}
+        ]
+    }"#;
+
+    #[test]
+    fn test_same_block_inscribe_and_transfer() {
+        let block = serde_json::from_str::<Block>(SAME_BLOCK_FIXTURE).unwrap();
+        let mut stored = Vec::new();
+        let applied = apply_block(&block, &mut stored);
+        assert_eq!(
+            applied
+                .iter()
+                .map(|(kind, id, satpoint)| (*kind, id.as_str(), satpoint.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                // Ordered by position among the outputs, not inscription number
+                ("reveal", "reveali1", "reveal:0:0"),
+                ("reveal", "reveali0", "reveal:1:0"),
+                // Transferred in the block it was inscribed in
+                ("reveal", "sendi0", "send:0:0"),
+                ("spend", "reveali0", "send:1:0"),
+                // A spend and a reveal on the same sat apply in inscription number order
+                ("spend", "sendi0", "batch:0:0"),
+                ("reveal", "batchi1", "batch:0:0"),
+                ("reveal", "batchi0", "batch:1:0"),
+            ]
+        );
+    }
+}
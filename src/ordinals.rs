@@ -1,4 +1,35 @@
-use serde::Deserialize;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Why an inscription body could not be fetched. `Unavailable` failures are transient and may
+/// succeed when retried.
+#[derive(Debug, PartialEq)]
+pub enum ContentFetchError {
+    Unavailable(String),
+    Rejected(String),
+}
+
+impl ContentFetchError {
+    fn from_status(status: StatusCode) -> ContentFetchError {
+        let message = format!("Failed with status code: {}", status);
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            ContentFetchError::Unavailable(message)
+        } else {
+            ContentFetchError::Rejected(message)
+        }
+    }
+}
+
+impl fmt::Display for ContentFetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentFetchError::Unavailable(message) | ContentFetchError::Rejected(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
 
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
@@ -29,15 +60,36 @@ pub struct Inscription {
     pub timestamp: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TransactionInscription {
     pub inscription_id: String,
     pub inscription_number: i64,
     pub content_type: Option<String>,
     pub content: Option<String>,
+    #[serde(default)]
+    pub content_encoding: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Where the inscription landed, as `<txid>:<vout>:<offset>`.
+    #[serde(default)]
+    pub satpoint: Option<String>,
+    /// The inscribed sat's number, when the ordinals server indexes sats.
+    #[serde(default)]
+    pub sat: Option<u64>,
+    /// ord's charms of the inscription, e.g. `reinscription`.
+    #[serde(default)]
+    pub charms: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl TransactionInscription {
+    /// The content type, which block data encodes as hex.
+    pub fn decoded_content_type(&self) -> Option<String> {
+        let content_type = hex::decode(self.content_type.as_ref()?).ok()?;
+        Some(String::from_utf8_lossy(&content_type).into_owned())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Transaction {
     pub transaction: String,
     pub inputs: Vec<(String, u64)>,
@@ -123,6 +175,26 @@ impl Ordinals {
         }
     }
 
+    /// Fetches unconfirmed transactions from `url`, which must return a JSON array of
+    /// transactions in the same format as the `transactions` of a block.
+    pub(crate) async fn get_mempool(
+        &mut self,
+        url: &str,
+    ) -> Result<Vec<Transaction>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = reqwest::Client::new()
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let transactions = response.json::<Vec<Transaction>>().await?;
+            Ok(transactions)
+        } else {
+            Err(format!("Failed with status code: {}", response.status()).into())
+        }
+    }
+
     pub(crate) async fn get_output(
         &mut self,
         outpoint: String,
@@ -184,27 +256,65 @@ impl Ordinals {
         }
     }
 
-    #[allow(unused)]
+    /// Fetches an inscription's raw body and its `content-encoding`, failing if the body is
+    /// larger than `max_size` bytes.
     pub(crate) async fn get_inscription_content(
         &mut self,
         inscription_id: String,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        max_size: usize,
+    ) -> Result<(Vec<u8>, Option<String>), ContentFetchError> {
         let url = format!("{}content/{}", self.base_url, inscription_id.as_str());
-        println!("{:?}", url);
-        let response = reqwest::get(url).await?;
+        let unavailable = |err: reqwest::Error| ContentFetchError::Unavailable(err.to_string());
+        let mut response = reqwest::get(url).await.map_err(unavailable)?;
 
         if response.status().is_success() {
-            let content = response.text().await?;
-            Ok(content)
+            let too_large = || format!("Content is larger than {} bytes", max_size);
+            if response
+                .content_length()
+                .is_some_and(|length| length > max_size as u64)
+            {
+                return Err(ContentFetchError::Rejected(too_large()));
+            }
+            let content_encoding = response
+                .headers()
+                .get(reqwest::header::CONTENT_ENCODING)
+                .and_then(|encoding| encoding.to_str().ok())
+                .map(str::to_string);
+
+            let mut content = Vec::new();
+            while let Some(chunk) = response.chunk().await.map_err(unavailable)? {
+                content.extend_from_slice(&chunk);
+                if content.len() > max_size {
+                    return Err(ContentFetchError::Rejected(too_large()));
+                }
+            }
+            Ok((content, content_encoding))
         } else {
-            Err(format!("Failed with status code: {}", response.status()).into())
+            Err(ContentFetchError::from_status(response.status()))
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ordinals::TransactionInscription;
+    use crate::ordinals::{ContentFetchError, TransactionInscription};
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_content_fetch_error_from_status() {
+        assert!(matches!(
+            ContentFetchError::from_status(StatusCode::SERVICE_UNAVAILABLE),
+            ContentFetchError::Unavailable(_)
+        ));
+        assert!(matches!(
+            ContentFetchError::from_status(StatusCode::TOO_MANY_REQUESTS),
+            ContentFetchError::Unavailable(_)
+        ));
+        assert!(matches!(
+            ContentFetchError::from_status(StatusCode::NOT_FOUND),
+            ContentFetchError::Rejected(_)
+        ));
+    }
 
     #[test]
     fn test_transaction_inscription_deserialization() {
@@ -238,6 +348,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decoded_content_type() {
+        let inscription = |content_type: Option<&str>| TransactionInscription {
+            inscription_id: "abci0".to_string(),
+            inscription_number: 0,
+            content_type: content_type.map(str::to_string),
+            content: None,
+            content_encoding: None,
+            parent: None,
+            satpoint: None,
+            sat: None,
+            charms: vec![],
+        };
+
+        assert_eq!(
+            inscription(Some(&hex::encode("text/plain"))).decoded_content_type(),
+            Some("text/plain".to_string())
+        );
+        assert_eq!(inscription(Some("text/plain")).decoded_content_type(), None);
+        assert_eq!(inscription(None).decoded_content_type(), None);
+    }
+
     #[test]
     fn test_transaction_inscription_null_content_deserialization() {
         let json_data = "
@@ -263,4 +395,31 @@ mod tests {
         assert_eq!(transaction_inscription.content_type, None);
         assert_eq!(transaction_inscription.content, None);
     }
+
+    #[test]
+    fn test_transaction_inscription_sat_deserialization() {
+        let json_data = "
+        {
+            \"inscription_id\": \"71e0f6dc87a473aa69787fff8e09e5eddfdca96e587928a5b1a25c0ae16dc0eei1\",
+            \"inscription_number\": 5,
+            \"content_type\": null,
+            \"content\": null,
+            \"satpoint\": \"71e0f6dc87a473aa69787fff8e09e5eddfdca96e587928a5b1a25c0ae16dc0ee:0:0\",
+            \"sat\": 1250000000,
+            \"charms\": [\"reinscription\", \"uncommon\"]
+        }";
+
+        let transaction_inscription: TransactionInscription =
+            serde_json::from_str(json_data).unwrap();
+
+        assert_eq!(transaction_inscription.sat, Some(1250000000));
+        assert_eq!(
+            transaction_inscription.charms,
+            vec!["reinscription".to_string(), "uncommon".to_string()]
+        );
+        assert_eq!(
+            transaction_inscription.satpoint.as_deref(),
+            Some("71e0f6dc87a473aa69787fff8e09e5eddfdca96e587928a5b1a25c0ae16dc0ee:0:0")
+        );
+    }
 }
@@ -0,0 +1,528 @@
+use crate::content::parse_media_type;
+use crate::util::bigdecimal_fractional_count;
+use bigdecimal::{BigDecimal, Zero};
+
+/// The validation rules of a meta protocol from `from_height` onwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleVersion {
+    pub from_height: i64,
+    /// Accepted deploy ticker byte lengths.
+    pub ticker_lengths: Vec<usize>,
+    /// Ticker byte lengths that are only valid when deployed with `"self_mint":"true"`.
+    pub self_mint_lengths: Vec<usize>,
+    pub max_decimals: i32,
+    /// The largest `max` a deploy may set.
+    pub max_supply: BigDecimal,
+    /// Media type essences (`type/subtype`) an inscription must have to be considered.
+    pub content_types: Vec<String>,
+    /// Whether cursed (negative numbered) inscriptions are considered. Inscriptions that ord
+    /// blesses after the jubilee already have positive numbers.
+    pub accept_cursed: bool,
+    /// Whether content types are parsed as MIME types that need a UTF-8 compatible charset, and
+    /// bodies are decompressed by their content encoding. Otherwise a content type containing
+    /// an accepted one is enough and bodies are parsed as inscribed.
+    pub strict_content: bool,
+}
+
+/// The supply, mint limit and decimals of a valid deploy after defaults are applied.
+#[derive(Debug, PartialEq)]
+pub struct ResolvedDeploy {
+    pub supply: BigDecimal,
+    pub limit_mint: BigDecimal,
+    pub decimals: i32,
+}
+
+/// Whether a deploy is accepted by the ticker rules, and if so whether its ticker is self-minted.
+#[derive(Debug, PartialEq)]
+pub enum TickerCheck {
+    Invalid,
+    Valid { self_mint: bool },
+}
+
+impl RuleVersion {
+    /// Checks the byte length of a deployed `ticker`, lowercased but otherwise as inscribed. The
+    /// canonical form is only a lookup key, and composing it can shorten the ticker.
+    pub fn check_ticker(&self, ticker: &str, self_mint: bool) -> TickerCheck {
+        let length = ticker.to_lowercase().len();
+        if self.self_mint_lengths.contains(&length) {
+            if self_mint {
+                TickerCheck::Valid { self_mint: true }
+            } else {
+                TickerCheck::Invalid
+            }
+        } else if self.ticker_lengths.contains(&length) {
+            TickerCheck::Valid { self_mint: false }
+        } else {
+            TickerCheck::Invalid
+        }
+    }
+
+    /// Applies the deploy defaults of the spec: a missing `dec` is the maximum number of
+    /// decimals and a missing `lim` is `max`. `max` is required, and `max` and `lim` must be
+    /// positive, at most `max_supply` and have no more than `dec` decimal places. For self-minted
+    /// tickers an explicit `"0"` for `max` or `lim` means `max_supply`. Returns `None` for an
+    /// invalid deploy.
+    pub fn resolve_deploy(
+        &self,
+        supply: Option<BigDecimal>,
+        limit_mint: Option<BigDecimal>,
+        decimal: Option<i32>,
+        self_mint: bool,
+    ) -> Option<ResolvedDeploy> {
+        let decimals = decimal.unwrap_or(self.max_decimals);
+        if !(0..=self.max_decimals).contains(&decimals) {
+            return None;
+        }
+
+        let resolve = |value: BigDecimal| {
+            let value = if self_mint && value == BigDecimal::zero() {
+                self.max_supply.clone()
+            } else {
+                value
+            };
+            (value > BigDecimal::zero()
+                && value <= self.max_supply
+                && bigdecimal_fractional_count(value.clone()) <= decimals as u32)
+                .then_some(value)
+        };
+        let supply = resolve(supply?)?;
+        let limit_mint = match limit_mint {
+            Some(limit_mint) => resolve(limit_mint)?,
+            None => supply.clone(),
+        };
+
+        Some(ResolvedDeploy {
+            supply,
+            limit_mint,
+            decimals,
+        })
+    }
+
+    /// Whether a content type's essence is one of `content_types` and its charset is UTF-8
+    /// compatible, or before `strict_content` whether it contains one of `content_types`.
+    pub fn accepts_content_type(&self, content_type: &str) -> bool {
+        if !self.strict_content {
+            return self
+                .content_types
+                .iter()
+                .any(|accepted| content_type.contains(accepted.as_str()));
+        }
+        parse_media_type(content_type).is_some_and(|media_type| {
+            media_type.is_utf8() && self.content_types.contains(&media_type.essence)
+        })
+    }
+
+    /// Whether a revealed inscription can carry an operation: its content type must be accepted,
+    /// and cursed inscriptions, which have negative numbers, only count with `accept_cursed`.
+    pub fn accepts_reveal(&self, number: i64, content_type: Option<&str>) -> bool {
+        (number >= 0 || self.accept_cursed)
+            && content_type.is_some_and(|content_type| self.accepts_content_type(content_type))
+    }
+
+    /// The content encoding a body is decoded with, which is none before `strict_content`.
+    pub fn content_encoding<'a>(&self, content_encoding: Option<&'a str>) -> Option<&'a str> {
+        content_encoding.filter(|_| self.strict_content)
+    }
+}
+
+/// A meta protocol and its rule versions, ordered by activation height. The first version is
+/// active from height 0.
+#[derive(Debug, Clone)]
+pub struct Protocol {
+    pub meta_protocol: String,
+    pub rule_versions: Vec<RuleVersion>,
+}
+
+impl Protocol {
+    /// The built-in rule versions of a meta protocol. brc-20 allows supplies up to the largest
+    /// 64-bit unsigned integer, accepts cursed inscriptions from the jubilee at block 824544 and
+    /// self-minted 5-byte tickers from block 912690.
+    pub fn new(meta_protocol: &str) -> Protocol {
+        let genesis = RuleVersion {
+            from_height: 0,
+            ticker_lengths: vec![4],
+            self_mint_lengths: vec![],
+            max_decimals: 18,
+            max_supply: BigDecimal::from(i64::MAX),
+            content_types: vec!["text/plain".to_string(), "application/json".to_string()],
+            accept_cursed: false,
+            strict_content: true,
+        };
+        let mut rule_versions = vec![genesis.clone()];
+        if meta_protocol == "brc-20" {
+            rule_versions[0].max_supply = BigDecimal::from(u64::MAX);
+            let genesis = rule_versions[0].clone();
+            rule_versions.push(RuleVersion {
+                from_height: 824544,
+                accept_cursed: true,
+                ..genesis.clone()
+            });
+            rule_versions.push(RuleVersion {
+                from_height: 912690,
+                self_mint_lengths: vec![5],
+                accept_cursed: true,
+                ..genesis
+            });
+        }
+        Protocol {
+            meta_protocol: meta_protocol.to_string(),
+            rule_versions,
+        }
+    }
+
+    /// The rules active at `height`.
+    pub fn rules_at(&self, height: i64) -> &RuleVersion {
+        self.rule_versions
+            .iter()
+            .rev()
+            .find(|rules| rules.from_height <= height)
+            .unwrap_or(&self.rule_versions[0])
+    }
+
+    /// Replaces the ticker lengths of every rule version with those of the ticker rule active at
+    /// the same height, adding versions at the ticker rules' activation heights.
+    pub fn with_ticker_rules(mut self, ticker_rules: &[TickerRule]) -> Protocol {
+        for rule in ticker_rules {
+            self.split_at(rule.from_height);
+        }
+        for rules in &mut self.rule_versions {
+            if let Some(ticker_rule) = ticker_rules
+                .iter()
+                .rev()
+                .find(|rule| rule.from_height <= rules.from_height)
+            {
+                rules.ticker_lengths = ticker_rule.lengths.clone();
+                rules.self_mint_lengths = ticker_rule.self_mint_lengths.clone();
+            }
+        }
+        self
+    }
+
+    /// Replaces the accepted content types of every rule version.
+    pub fn with_content_types(mut self, content_types: &[String]) -> Protocol {
+        for rules in &mut self.rule_versions {
+            rules.content_types = content_types.to_vec();
+        }
+        self
+    }
+
+    /// Applies the strict content rules from `height` onwards, and only from then.
+    pub fn with_strict_content_height(mut self, height: i64) -> Protocol {
+        self.split_at(height);
+        for rules in &mut self.rule_versions {
+            rules.strict_content = rules.from_height >= height;
+        }
+        self
+    }
+
+    /// Accepts cursed inscriptions from `jubilee_height` onwards, and only from then.
+    pub fn with_jubilee_height(mut self, jubilee_height: i64) -> Protocol {
+        self.split_at(jubilee_height);
+        for rules in &mut self.rule_versions {
+            rules.accept_cursed = rules.from_height >= jubilee_height;
+        }
+        self
+    }
+
+    /// Starts a new rule version at `height`, identical to the one active there.
+    fn split_at(&mut self, height: i64) {
+        if self
+            .rule_versions
+            .iter()
+            .any(|rules| rules.from_height == height)
+        {
+            return;
+        }
+        let rules = RuleVersion {
+            from_height: height,
+            ..self.rules_at(height).clone()
+        };
+        self.rule_versions.push(rules);
+        self.rule_versions.sort_by_key(|rules| rules.from_height);
+    }
+}
+
+/// A configured override of the ticker byte lengths accepted from `from_height` onwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickerRule {
+    pub from_height: i64,
+    pub lengths: Vec<usize>,
+    pub self_mint_lengths: Vec<usize>,
+}
+
+impl TickerRule {
+    /// Parses rules such as `0:4;912690:4/5`: `;` separated `<height>:<lengths>` entries with
+    /// comma separated byte lengths, where the lengths after `/` require `self_mint`.
+    pub fn parse_list(rules: &str) -> Result<Vec<TickerRule>, String> {
+        let lengths = |lengths: &str| {
+            lengths
+                .split(',')
+                .map(str::trim)
+                .filter(|length| !length.is_empty())
+                .map(|length| {
+                    length
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid ticker length '{}'", length))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut rules = rules
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| {
+                let (height, rule_lengths) = rule
+                    .split_once(':')
+                    .ok_or(format!("Ticker rule '{}' has no activation height", rule))?;
+                let (plain, self_mint) = rule_lengths.split_once('/').unwrap_or((rule_lengths, ""));
+                Ok(TickerRule {
+                    from_height: height
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid activation height '{}'", height))?,
+                    lengths: lengths(plain)?,
+                    self_mint_lengths: lengths(self_mint)?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        rules.sort_by_key(|rule| rule.from_height);
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{Protocol, ResolvedDeploy, TickerCheck, TickerRule};
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_ticker_rules() {
+        assert_eq!(
+            TickerRule::parse_list("912690:4/5; 0:4"),
+            Ok(vec![
+                TickerRule {
+                    from_height: 0,
+                    lengths: vec![4],
+                    self_mint_lengths: vec![],
+                },
+                TickerRule {
+                    from_height: 912690,
+                    lengths: vec![4],
+                    self_mint_lengths: vec![5],
+                },
+            ])
+        );
+        assert!(TickerRule::parse_list("4").is_err());
+        assert!(TickerRule::parse_list("0:four").is_err());
+    }
+
+    #[test]
+    fn test_check_ticker_activation() {
+        let protocol = Protocol::new("brc-20");
+        let check =
+            |height, ticker, self_mint| protocol.rules_at(height).check_ticker(ticker, self_mint);
+
+        assert_eq!(
+            check(900000, "ordi", false),
+            TickerCheck::Valid { self_mint: false }
+        );
+        assert_eq!(
+            check(912690, "ordi", true),
+            TickerCheck::Valid { self_mint: false }
+        );
+        assert_eq!(check(912689, "abcde", true), TickerCheck::Invalid);
+        assert_eq!(check(912690, "abcde", false), TickerCheck::Invalid);
+        assert_eq!(
+            check(912690, "abcde", true),
+            TickerCheck::Valid { self_mint: true }
+        );
+        assert_eq!(
+            Protocol::new("ltc-20")
+                .rules_at(3000000)
+                .check_ticker("abcde", true),
+            TickerCheck::Invalid
+        );
+        // Byte length, not character count
+        assert_eq!(
+            check(0, "\u{1f680}", false),
+            TickerCheck::Valid { self_mint: false }
+        );
+        // Decomposed as inscribed: 5 bytes, though its canonical form is 4
+        assert_eq!(check(900000, "E\u{301}ab", false), TickerCheck::Invalid);
+        assert_eq!(
+            check(912690, "E\u{301}ab", true),
+            TickerCheck::Valid { self_mint: true }
+        );
+    }
+
+    #[test]
+    fn test_rule_versions_with_ticker_rules() {
+        let protocol = Protocol::new("brc-20")
+            .with_ticker_rules(&TickerRule::parse_list("0:4;100:4,6").unwrap());
+
+        assert_eq!(
+            protocol
+                .rule_versions
+                .iter()
+                .map(|rules| (rules.from_height, rules.ticker_lengths.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, vec![4]),
+                (100, vec![4, 6]),
+                (824544, vec![4, 6]),
+                (912690, vec![4, 6])
+            ]
+        );
+        assert!(protocol.rules_at(912690).self_mint_lengths.is_empty());
+        assert_eq!(protocol.rules_at(99).max_decimals, 18);
+        assert!(protocol
+            .rules_at(0)
+            .accepts_content_type("text/plain;charset=utf-8"));
+        assert!(protocol
+            .rules_at(0)
+            .accepts_content_type("Application/JSON; charset=\"utf-8\""));
+        assert!(!protocol.rules_at(0).accepts_content_type("image/png"));
+        assert!(!protocol.rules_at(0).accepts_content_type("text/plainx"));
+        assert!(!protocol
+            .rules_at(0)
+            .accepts_content_type("text/plain;charset=utf-16"));
+        assert!(!protocol.rules_at(0).accepts_content_type("text/markdown"));
+
+        let protocol =
+            protocol.with_content_types(&["text/plain".to_string(), "text/markdown".to_string()]);
+        assert!(protocol
+            .rules_at(912690)
+            .accepts_content_type("text/markdown"));
+        assert!(!protocol
+            .rules_at(0)
+            .accepts_content_type("application/json"));
+    }
+
+    #[test]
+    fn test_strict_content_height() {
+        let protocol = Protocol::new("ltc-20").with_strict_content_height(2500000);
+        let legacy = protocol.rules_at(2499999);
+        let strict = protocol.rules_at(2500000);
+
+        assert!(legacy.accepts_content_type("text/plain;charset=utf-16"));
+        assert!(legacy.accepts_content_type("text/plainx"));
+        assert!(!legacy.accepts_content_type("Application/JSON"));
+        assert_eq!(legacy.content_encoding(Some("br")), None);
+
+        assert!(!strict.accepts_content_type("text/plain;charset=utf-16"));
+        assert!(!strict.accepts_content_type("text/plainx"));
+        assert!(strict.accepts_content_type("Application/JSON"));
+        assert_eq!(strict.content_encoding(Some("br")), Some("br"));
+
+        assert!(Protocol::new("ltc-20").rules_at(0).strict_content);
+    }
+
+    #[test]
+    fn test_accepts_reveal() {
+        let brc20 = Protocol::new("brc-20");
+        let before_jubilee = brc20.rules_at(824543);
+        let after_jubilee = brc20.rules_at(824544);
+
+        assert!(before_jubilee.accepts_reveal(5, Some("text/plain")));
+        assert!(!before_jubilee.accepts_reveal(-5, Some("text/plain")));
+        assert!(after_jubilee.accepts_reveal(-5, Some("text/plain")));
+        assert!(!after_jubilee.accepts_reveal(5, Some("image/png")));
+        assert!(!after_jubilee.accepts_reveal(5, None));
+    }
+
+    #[test]
+    fn test_jubilee_height() {
+        let brc20 = Protocol::new("brc-20");
+        assert!(!brc20.rules_at(824543).accept_cursed);
+        assert!(brc20.rules_at(824544).accept_cursed);
+        assert!(brc20.rules_at(912690).accept_cursed);
+
+        let ltc20 = Protocol::new("ltc-20");
+        assert!(!ltc20.rules_at(i64::MAX).accept_cursed);
+
+        let ltc20 = ltc20.with_jubilee_height(2600000);
+        assert!(!ltc20.rules_at(2599999).accept_cursed);
+        assert!(ltc20.rules_at(2600000).accept_cursed);
+
+        let brc20 = brc20.with_jubilee_height(900000);
+        assert!(!brc20.rules_at(824544).accept_cursed);
+        assert!(brc20.rules_at(912690).accept_cursed);
+    }
+
+    #[test]
+    fn test_resolve_deploy() {
+        let rules = Protocol::new("ltc-20").rules_at(0).clone();
+        let number = |value: &str| Some(BigDecimal::from_str(value).unwrap());
+        let resolve = |max: &str, lim: &str, dec: Option<i32>, self_mint| {
+            rules.resolve_deploy(
+                (!max.is_empty()).then(|| BigDecimal::from_str(max).unwrap()),
+                (!lim.is_empty()).then(|| BigDecimal::from_str(lim).unwrap()),
+                dec,
+                self_mint,
+            )
+        };
+        let resolved = |max: i64, lim: i64, decimals| {
+            Some(ResolvedDeploy {
+                supply: BigDecimal::from(max),
+                limit_mint: BigDecimal::from(lim),
+                decimals,
+            })
+        };
+
+        // max and lim given, missing lim, missing max
+        assert_eq!(resolve("1000", "10", None, false), resolved(1000, 10, 18));
+        assert_eq!(resolve("1000", "", None, false), resolved(1000, 1000, 18));
+        assert_eq!(resolve("", "10", None, false), None);
+        assert_eq!(resolve("", "", None, false), None);
+
+        // Zero and negative values
+        assert_eq!(resolve("0", "10", None, false), None);
+        assert_eq!(resolve("1000", "0", None, false), None);
+        assert_eq!(resolve("0", "", None, false), None);
+        assert_eq!(resolve("-1", "", None, false), None);
+        assert_eq!(resolve("1000", "-1", None, false), None);
+
+        // Limits above max are allowed, supplies above the protocol maximum are not
+        assert_eq!(
+            resolve("1000", "2000", None, false),
+            resolved(1000, 2000, 18)
+        );
+        assert_eq!(
+            resolve(&i64::MAX.to_string(), "", None, false),
+            resolved(i64::MAX, i64::MAX, 18)
+        );
+        assert_eq!(resolve("9223372036854775808", "", None, false), None);
+
+        // Decimals
+        assert_eq!(resolve("1000", "", Some(0), false), resolved(1000, 1000, 0));
+        assert_eq!(resolve("1000", "", Some(19), false), None);
+        assert_eq!(resolve("1000", "", Some(-1), false), None);
+        assert_eq!(resolve("1000.5", "", Some(0), false), None);
+        assert_eq!(resolve("1000", "0.25", Some(1), false), None);
+        assert_eq!(
+            resolve("1000.5", "0.25", Some(2), false).map(|d| d.limit_mint),
+            Some(number("0.25").unwrap())
+        );
+
+        // Self-minted tickers treat zero as the protocol maximum
+        let max = BigDecimal::from(u64::MAX);
+        let brc20 = Protocol::new("brc-20").rules_at(912690).clone();
+        assert_eq!(
+            brc20.resolve_deploy(number("0"), number("0"), None, true),
+            Some(ResolvedDeploy {
+                supply: max.clone(),
+                limit_mint: max.clone(),
+                decimals: 18,
+            })
+        );
+        assert_eq!(
+            brc20
+                .resolve_deploy(number("0"), None, None, true)
+                .map(|d| d.limit_mint),
+            Some(max)
+        );
+        assert_eq!(brc20.resolve_deploy(number("0"), None, None, false), None);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::indexer::Indexer;
+use crate::util::canonical_ticker;
+use std::time::Instant;
+
+#[derive(Debug, PartialEq)]
+pub enum ReplayEvent {
+    Reveal(i32),
+    Spend(i32),
+}
+
+/// `(id, height, tx_index, tx_offset, inscription_num, spent_height, spent_tx_index,
+/// spent_tx_offset)` of a stored inscription.
+pub type InscriptionOrderKey = (
+    i32,
+    i64,
+    Option<i64>,
+    Option<i64>,
+    i64,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+);
+
+/// Orders the stored inscriptions into the sequence `process_block` originally applied them in:
+/// by block height, then transaction position, then the offset of the event's sat among the
+/// transaction's outputs, then inscription number. Rows indexed before sat offsets were recorded
+/// handle a transaction's spends before its reveals, and rows indexed before transaction
+/// positions were recorded reveal at the start of their block and spend at its end.
+pub fn replay_order(keys: &[InscriptionOrderKey]) -> Vec<ReplayEvent> {
+    let mut events = Vec::new();
+    for (
+        id,
+        height,
+        tx_index,
+        tx_offset,
+        inscription_num,
+        spent_height,
+        spent_tx_index,
+        spent_tx_offset,
+    ) in keys
+    {
+        events.push((
+            (
+                *height,
+                tx_index.unwrap_or(0),
+                tx_offset.map_or((-1, 1), |offset| (offset, 0)),
+                *inscription_num,
+            ),
+            ReplayEvent::Reveal(*id),
+        ));
+        if let Some(spent_height) = spent_height {
+            events.push((
+                (
+                    *spent_height,
+                    spent_tx_index.unwrap_or(i64::MAX),
+                    spent_tx_offset.map_or((-1, 0), |offset| (offset, 0)),
+                    *inscription_num,
+                ),
+                ReplayEvent::Spend(*id),
+            ));
+        }
+    }
+    events.sort_by_key(|(key, _)| *key);
+    events.into_iter().map(|(_, event)| event).collect()
+}
+
+impl Indexer {
+    /// Rebuilds `balances`, `tracker` and `history` purely from the `inscriptions` table without
+    /// fetching any blocks. The rebuild runs in a single transaction.
+    pub(crate) fn replay(&mut self) -> Result<usize, diesel::result::Error> {
+        let start_time = Instant::now();
+        let count = self.in_transaction(|indexer| indexer.replay_inscriptions())?;
+        log::info!(
+            "Replayed {} inscription events in {:?}",
+            count,
+            start_time.elapsed()
+        );
+        Ok(count)
+    }
+
+    /// Reverts all indexed state at heights `>= height` so indexing resumes from `height`.
+    ///
+    /// Inscriptions revealed at or above `height` are deleted, transfer inscriptions spent at or
+    /// above it become unspent again, and the derived state is rebuilt from what remains. This is
+    /// the undo step for both operator-driven reindexing and block reorganisations.
+    pub(crate) fn rollback(&mut self, height: i64) -> Result<(), diesel::result::Error> {
+        let start_time = Instant::now();
+        self.in_transaction(|indexer| {
+            let deleted = indexer.database.delete_inscriptions_from_height(height)?;
+            let unspent = indexer.database.unspend_inscriptions_from_height(height)?;
+            // Re-indexed blocks write their events again under the same idempotency keys
+            indexer.database.delete_outbox_from_height(height)?;
+            indexer.database.delete_blocks_from_height(height)?;
+            indexer.database.delete_dead_letters_from_height(height)?;
+            log::info!(
+                "Rolling back to block {}: {} inscriptions deleted, {} transfers unspent",
+                height,
+                deleted,
+                unspent
+            );
+            indexer.replay_inscriptions()?;
+            indexer
+                .database
+                .update_status("last_height".to_string(), (height - 1).to_string())?;
+            Ok(())
+        })?;
+        log::info!(
+            "Rolled back to block {} in {:?}",
+            height,
+            start_time.elapsed()
+        );
+        Ok(())
+    }
+
+    fn in_transaction<T>(
+        &mut self,
+        f: impl FnOnce(&mut Indexer) -> Result<T, diesel::result::Error>,
+    ) -> Result<T, diesel::result::Error> {
+        self.database.begin_transaction()?;
+        match f(self) {
+            Ok(value) => {
+                self.database.commit_transaction()?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.database.rollback_transaction()?;
+                Err(err)
+            }
+        }
+    }
+
+    fn replay_inscriptions(&mut self) -> Result<usize, diesel::result::Error> {
+        self.database.reset_state()?;
+        // Every ticker-keyed table is rebuilt from these keys
+        let rekeyed = self.database.rekey_inscription_tickers(canonical_ticker)?;
+        if rekeyed > 0 {
+            log::info!("Updated the ticker key of {} inscriptions", rekeyed);
+        }
+
+        let events = replay_order(&self.database.get_inscription_order_keys()?);
+        for (index, event) in events.iter().enumerate() {
+            match event {
+                ReplayEvent::Reveal(id) => {
+                    let inscription = self.database.get_inscription(*id)?;
+                    self.process_inscription(&inscription)?;
+                }
+                ReplayEvent::Spend(id) => {
+                    let inscription = self.database.get_inscription(*id)?;
+                    if inscription.valid.unwrap_or_default() {
+                        self.process_inscription_transfer(&inscription)?;
+                    }
+                }
+            }
+            if (index + 1) % 10000 == 0 {
+                log::info!("Replayed {}/{} inscription events", index + 1, events.len());
+            }
+        }
+        // Replayed operations were already delivered when they were first indexed, but they
+        // still determine each block's state hash
+        let replayed_events = std::mem::take(&mut self.events);
+        self.rebuild_block_hashes(replayed_events)?;
+        Ok(events.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::replay::{replay_order, ReplayEvent};
+
+    #[test]
+    fn test_replay_order_same_block_inscribe_and_transfer() {
+        let keys = vec![
+            (1, 10, Some(3), None, 100, Some(10), Some(5), None),
+            (2, 10, Some(4), None, 101, None, None, None),
+            (3, 9, Some(1), None, 99, Some(10), Some(3), None),
+        ];
+        assert_eq!(
+            replay_order(&keys),
+            vec![
+                ReplayEvent::Reveal(3),
+                ReplayEvent::Spend(3),
+                ReplayEvent::Reveal(1),
+                ReplayEvent::Reveal(2),
+                ReplayEvent::Spend(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_order_legacy_rows() {
+        let keys = vec![
+            (1, 10, None, None, 100, Some(11), None, None),
+            (2, 11, None, None, 101, None, None, None),
+        ];
+        assert_eq!(
+            replay_order(&keys),
+            vec![
+                ReplayEvent::Reveal(1),
+                ReplayEvent::Reveal(2),
+                ReplayEvent::Spend(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_order_within_transaction() {
+        // Transaction 2 of block 10 reveals 2 on output 1 and 3 on output 0, and sends 1 to the
+        // same sat as 3
+        let keys = vec![
+            (1, 9, Some(1), Some(0), 50, Some(10), Some(2), Some(0)),
+            (2, 10, Some(2), Some(546), 101, None, None, None),
+            (3, 10, Some(2), Some(0), 102, None, None, None),
+        ];
+        assert_eq!(
+            replay_order(&keys),
+            vec![
+                ReplayEvent::Reveal(1),
+                ReplayEvent::Spend(1),
+                ReplayEvent::Reveal(3),
+                ReplayEvent::Reveal(2),
+            ]
+        );
+    }
+}
@@ -10,6 +10,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    balance_history (id) {
+        id -> Int4,
+        address -> Text,
+        ticker -> Text,
+        balance -> Numeric,
+        transfer_balance -> Numeric,
+        height -> Int8,
+    }
+}
+
+diesel::table! {
+    blocks (height) {
+        height -> Int8,
+        block_hash -> Text,
+        event_count -> Int4,
+        event_hash -> Text,
+        cumulative_hash -> Text,
+    }
+}
+
+diesel::table! {
+    dead_letters (id) {
+        id -> Int4,
+        height -> Int8,
+        tx_id -> Text,
+        inscription_id -> Text,
+        payload -> Text,
+        error -> Text,
+    }
+}
+
 diesel::table! {
     history (id) {
         id -> Int4,
@@ -24,6 +56,8 @@ diesel::table! {
         inscription_num -> Int8,
         height -> Int8,
         timestamp -> Int8,
+        amount_requested -> Numeric,
+        partial_mint -> Bool,
     }
 }
 
@@ -52,6 +86,52 @@ diesel::table! {
         spent_offset -> Nullable<Int8>,
         spent_height -> Nullable<Int8>,
         spent_timestamp -> Nullable<Int8>,
+        tx_index -> Nullable<Int8>,
+        spent_tx_index -> Nullable<Int8>,
+        original_ticker -> Text,
+        parent_id -> Nullable<Text>,
+        self_mint -> Bool,
+        output_offset -> Int8,
+        spent_output -> Nullable<Text>,
+        tx_offset -> Nullable<Int8>,
+        spent_tx_offset -> Nullable<Int8>,
+        sat -> Nullable<Int8>,
+        invalid_reason -> Nullable<Text>,
+        burned -> Bool,
+    }
+}
+
+diesel::table! {
+    mint_activity (id) {
+        id -> Int4,
+        ticker -> Text,
+        height -> Int8,
+        timestamp -> Int8,
+        mints -> Int8,
+        amount -> Numeric,
+    }
+}
+
+diesel::table! {
+    outbox (id) {
+        id -> Int8,
+        idempotency_key -> Text,
+        height -> Int8,
+        payload -> Text,
+        delivered -> Bool,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    pending_balances (id) {
+        id -> Int4,
+        address -> Text,
+        ticker -> Text,
+        balance -> Numeric,
+        transfer_balance -> Numeric,
+        tx_id -> Text,
     }
 }
 
@@ -77,7 +157,29 @@ diesel::table! {
         transactions -> Int8,
         inscription_mint_start -> Nullable<Int8>,
         inscription_mint_end -> Nullable<Int8>,
+        display_ticker -> Text,
+        self_mint -> Bool,
+        valid_mints -> Int8,
+        invalid_mints -> Int8,
+        unique_minters -> Int8,
+        first_mint_height -> Nullable<Int8>,
+        first_mint_timestamp -> Nullable<Int8>,
+        last_mint_height -> Nullable<Int8>,
+        last_mint_timestamp -> Nullable<Int8>,
+        burned -> Numeric,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(balances, history, inscriptions, status, tracker,);
+diesel::allow_tables_to_appear_in_same_query!(
+    balance_history,
+    balances,
+    blocks,
+    dead_letters,
+    history,
+    inscriptions,
+    mint_activity,
+    outbox,
+    pending_balances,
+    status,
+    tracker,
+);
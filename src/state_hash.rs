@@ -0,0 +1,185 @@
+use crate::events::{Event, EventType};
+use crate::indexer::Indexer;
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, Signed};
+use diesel::{OptionalExtension, QueryResult};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+const EVENT_SEPARATOR: &str = "|";
+
+/// Formats `amount` as a plain decimal without trailing zeros or exponent, e.g. `100` or `1.5`.
+pub fn plain_decimal(amount: &BigDecimal) -> String {
+    let (digits, scale) = amount.normalized().into_bigint_and_exponent();
+    if scale <= 0 {
+        return (digits * BigInt::from(10).pow(scale.unsigned_abs() as u32)).to_string();
+    }
+    let sign = if digits.is_negative() { "-" } else { "" };
+    let scale = scale as usize;
+    let digits = format!("{:0>width$}", digits.abs(), width = scale + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    format!("{}{}.{}", sign, integer, fraction)
+}
+
+/// Serialises the balance-changing fields of an event. Amounts are normalised so that equal
+/// values always produce the same string regardless of their stored scale.
+pub fn event_string(event: &Event) -> String {
+    format!(
+        "{};{};{};{};{};{}",
+        event.event.as_str(),
+        event.inscription_id,
+        event.address_sender.clone().unwrap_or_default(),
+        event.address_receiver.clone().unwrap_or_default(),
+        event.ticker,
+        event.amount.as_ref().map(plain_decimal).unwrap_or_default()
+    )
+}
+
+/// Hashes the ordered list of a block's balance-changing events. `invalid` events change no
+/// state and are left out.
+pub fn block_event_hash(events: &[Event]) -> String {
+    let events = events
+        .iter()
+        .filter(|event| event.event != EventType::Invalid)
+        .map(event_string)
+        .collect::<Vec<_>>()
+        .join(EVENT_SEPARATOR);
+    hex::encode(Sha256::digest(events.as_bytes()))
+}
+
+/// Chains a block's event hash onto the cumulative hash of the previous block.
+pub fn cumulative_hash(previous_cumulative_hash: &str, event_hash: &str) -> String {
+    hex::encode(Sha256::digest(
+        format!("{}{}", previous_cumulative_hash, event_hash).as_bytes(),
+    ))
+}
+
+impl Indexer {
+    /// Stores the event hash and cumulative state hash of the block at `height`. Two indexers
+    /// agree on all state up to a height if, and only if, their cumulative hashes match there.
+    /// Only the first hashed block starts from an empty hash; any later block fails when the
+    /// previous block's hash is missing.
+    pub(crate) fn record_block_hash(
+        &mut self,
+        height: i64,
+        block_hash: String,
+        events: &[Event],
+    ) -> QueryResult<String> {
+        let previous = match self.database.get_block(height - 1).optional()? {
+            Some(block) => block.cumulative_hash,
+            None if !self.database.has_blocks_before(height)? => String::new(),
+            None => {
+                log::error!("No state hash for block {}", height - 1);
+                return Err(diesel::result::Error::NotFound);
+            }
+        };
+        let event_hash = block_event_hash(events);
+        let cumulative = cumulative_hash(&previous, &event_hash);
+        self.database.create_block(
+            height,
+            block_hash,
+            events.len() as i32,
+            event_hash,
+            cumulative.clone(),
+        )?;
+        Ok(cumulative)
+    }
+
+    /// Recomputes the hashes of every stored block from `events`, e.g. after a replay changed
+    /// the events produced by earlier blocks.
+    pub(crate) fn rebuild_block_hashes(&mut self, events: Vec<Event>) -> QueryResult<usize> {
+        let mut events_by_height: BTreeMap<i64, Vec<Event>> = BTreeMap::new();
+        for event in events {
+            events_by_height
+                .entry(event.height)
+                .or_default()
+                .push(event);
+        }
+
+        let blocks = self.database.get_blocks()?;
+        let mut previous = String::new();
+        for block in &blocks {
+            let events = events_by_height.remove(&block.height).unwrap_or_default();
+            let event_hash = block_event_hash(&events);
+            let cumulative = cumulative_hash(&previous, &event_hash);
+            self.database.create_block(
+                block.height,
+                block.block_hash.clone(),
+                events.len() as i32,
+                event_hash,
+                cumulative.clone(),
+            )?;
+            previous = cumulative;
+        }
+        Ok(blocks.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::events::{Event, EventType};
+    use crate::state_hash::{block_event_hash, cumulative_hash, event_string, plain_decimal};
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    fn event(amount: &str) -> Event {
+        Event {
+            sequence: 7,
+            idempotency_key: "abci0:mint".to_string(),
+            height: 10,
+            event: EventType::Mint,
+            ticker: "lite".to_string(),
+            inscription_id: "abci0".to_string(),
+            inscription_num: 5,
+            tx_id: "abc".to_string(),
+            address_sender: None,
+            address_receiver: Some("alice".to_string()),
+            amount: Some(BigDecimal::from_str(amount).unwrap()),
+            timestamp: 0,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_event_string_normalises_amount() {
+        assert_eq!(event_string(&event("1.50")), "mint;abci0;;alice;lite;1.5");
+        assert_eq!(event_string(&event("100")), event_string(&event("100.000")));
+        assert_eq!(event_string(&event("100")), "mint;abci0;;alice;lite;100");
+    }
+
+    #[test]
+    fn test_plain_decimal() {
+        let plain = |amount: &str| plain_decimal(&BigDecimal::from_str(amount).unwrap());
+        assert_eq!(plain("100"), "100");
+        assert_eq!(plain("1E+2"), "100");
+        assert_eq!(plain("21000000.000"), "21000000");
+        assert_eq!(plain("1.50"), "1.5");
+        assert_eq!(plain("0.000000000000000001"), "0.000000000000000001");
+        assert_eq!(plain("-0.05"), "-0.05");
+        assert_eq!(plain("0"), "0");
+    }
+
+    #[test]
+    fn test_block_hashes() {
+        assert_eq!(
+            block_event_hash(&[]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_ne!(
+            block_event_hash(&[event("1")]),
+            block_event_hash(&[event("2")])
+        );
+        let invalid = Event {
+            event: EventType::Invalid,
+            ..event("1")
+        };
+        assert_eq!(
+            block_event_hash(&[event("1"), invalid]),
+            block_event_hash(&[event("1")])
+        );
+        assert_ne!(
+            cumulative_hash("", &block_event_hash(&[])),
+            cumulative_hash("00", &block_event_hash(&[]))
+        );
+    }
+}
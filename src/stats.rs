@@ -0,0 +1,133 @@
+use crate::indexer::Indexer;
+use crate::models::MintActivity;
+use bigdecimal::{BigDecimal, Zero};
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MintRate {
+    pub from_height: i64,
+    pub to_height: i64,
+    pub mints: i64,
+    pub amount: BigDecimal,
+}
+
+/// The share of `supply` that has been minted, as a percentage rounded to 8 decimal places.
+pub fn percent_minted(supply: &BigDecimal, supply_minted: &BigDecimal) -> BigDecimal {
+    if *supply == BigDecimal::zero() {
+        return BigDecimal::zero();
+    }
+    (supply_minted * BigDecimal::from(100) / supply).round(8)
+}
+
+/// Groups per-block mint activity into windows of `blocks` heights, aligned to multiples of
+/// `blocks`. Windows without any mints are left out.
+pub fn mint_rate(activity: &[MintActivity], blocks: i64) -> Vec<MintRate> {
+    let mut rates: Vec<MintRate> = Vec::new();
+    for row in activity {
+        let from_height = row.height - row.height.rem_euclid(blocks);
+        match rates.last_mut() {
+            Some(rate) if rate.from_height == from_height => {
+                rate.mints += row.mints;
+                rate.amount += row.amount.clone();
+            }
+            _ => rates.push(MintRate {
+                from_height,
+                to_height: from_height + blocks - 1,
+                mints: row.mints,
+                amount: row.amount.clone(),
+            }),
+        }
+    }
+    rates
+}
+
+impl Indexer {
+    /// Writes the mint statistics of `ticker` as a JSON object, followed by one JSON line per
+    /// window of `blocks` heights with mints in it.
+    pub(crate) fn mint_stats(
+        &mut self,
+        ticker: String,
+        blocks: i64,
+        out: &mut impl Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tracker = self.database.get_tracker(ticker.clone())?;
+        let activity = self.database.get_mint_activity(ticker)?;
+
+        writeln!(
+            out,
+            "{}",
+            serde_json::json!({
+                "ticker": tracker.display_ticker,
+                "supply": tracker.supply,
+                "supply_minted": tracker.supply_minted,
+                "burned": tracker.burned,
+                "circulating_supply": tracker.supply_minted.clone() - tracker.burned.clone(),
+                "percent_minted": percent_minted(&tracker.supply, &tracker.supply_minted),
+                "valid_mints": tracker.valid_mints,
+                "invalid_mints": tracker.invalid_mints,
+                "unique_minters": tracker.unique_minters,
+                "first_mint_height": tracker.first_mint_height,
+                "first_mint_timestamp": tracker.first_mint_timestamp,
+                "last_mint_height": tracker.last_mint_height,
+                "last_mint_timestamp": tracker.last_mint_timestamp,
+            })
+        )?;
+        for rate in mint_rate(&activity, blocks) {
+            writeln!(out, "{}", serde_json::to_string(&rate)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::MintActivity;
+    use crate::stats::{mint_rate, percent_minted};
+    use bigdecimal::{BigDecimal, Zero};
+    use std::str::FromStr;
+
+    fn activity(height: i64, mints: i64, amount: i64) -> MintActivity {
+        MintActivity {
+            id: 0,
+            ticker: "lite".to_string(),
+            height,
+            timestamp: 0,
+            mints,
+            amount: BigDecimal::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_mint_rate_and_percent() {
+        let rates = mint_rate(
+            &[
+                activity(98, 1, 10),
+                activity(100, 2, 20),
+                activity(199, 3, 30),
+                activity(350, 1, 5),
+            ],
+            100,
+        )
+        .into_iter()
+        .map(|rate| (rate.from_height, rate.to_height, rate.mints, rate.amount))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            rates,
+            vec![
+                (0, 99, 1, BigDecimal::from(10)),
+                (100, 199, 5, BigDecimal::from(50)),
+                (300, 399, 1, BigDecimal::from(5)),
+            ]
+        );
+        assert_eq!(
+            percent_minted(&BigDecimal::from(3), &BigDecimal::from(1)),
+            BigDecimal::from_str("33.33333333").unwrap()
+        );
+        assert_eq!(
+            percent_minted(&BigDecimal::zero(), &BigDecimal::zero()),
+            BigDecimal::zero()
+        );
+    }
+}
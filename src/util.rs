@@ -1,5 +1,6 @@
 use bigdecimal::BigDecimal;
 use chrono::prelude::*;
+use unicode_normalization::UnicodeNormalization;
 
 pub fn string_to_timestamp(timestamp_str: &str) -> Option<i64> {
     if let Ok(parsed_time) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S %Z") {
@@ -9,6 +10,27 @@ pub fn string_to_timestamp(timestamp_str: &str) -> Option<i64> {
     }
 }
 
+/// Returns the key tickers are matched on: the NFC normalised, lowercased form of `tick`, so
+/// that differently cased or differently composed spellings refer to the same token.
+pub fn canonical_ticker(tick: &str) -> String {
+    tick.nfc()
+        .collect::<String>()
+        .to_lowercase()
+        .nfc()
+        .collect()
+}
+
+/// Splits a `<txid>:<vout>:<offset>` satpoint into its outpoint and the offset of the sat
+/// within that output.
+pub fn parse_satpoint(satpoint: &str) -> Option<(String, u64)> {
+    let (outpoint, offset) = satpoint.rsplit_once(':')?;
+    let (txid, vout) = outpoint.split_once(':')?;
+    if txid.is_empty() || vout.parse::<u32>().is_err() {
+        return None;
+    }
+    Some((outpoint.to_string(), offset.parse().ok()?))
+}
+
 pub fn bigdecimal_fractional_count(big_decimal: BigDecimal) -> u32 {
     if big_decimal.fractional_digit_count() <= 0 {
         return 0;
@@ -21,3 +43,47 @@ pub fn bigdecimal_fractional_count(big_decimal: BigDecimal) -> u32 {
         0
     }) as u32
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::util::{canonical_ticker, parse_satpoint};
+
+    #[test]
+    fn test_canonical_ticker_latin() {
+        assert_eq!(canonical_ticker("LiTe"), "lite");
+        assert_eq!(canonical_ticker("lite"), canonical_ticker("LITE"));
+    }
+
+    #[test]
+    fn test_canonical_ticker_composition() {
+        // "É" precomposed and as "E" + combining acute accent
+        assert_eq!(canonical_ticker("\u{c9}tat"), "\u{e9}tat");
+        assert_eq!(canonical_ticker("E\u{301}tat"), "\u{e9}tat");
+    }
+
+    #[test]
+    fn test_canonical_ticker_emoji_and_non_latin() {
+        assert_eq!(canonical_ticker("\u{1f680}BTC"), "\u{1f680}btc");
+        assert_eq!(canonical_ticker("\u{1f44d}\u{1f3fd}"), "\u{1f44d}\u{1f3fd}");
+        assert_eq!(
+            canonical_ticker("\u{41b}\u{410}\u{419}\u{422}"),
+            "\u{43b}\u{430}\u{439}\u{442}"
+        );
+        assert_eq!(canonical_ticker("\u{72d7}\u{72d7}"), "\u{72d7}\u{72d7}");
+        // Hangul syllable and its conjoining jamo
+        assert_eq!(canonical_ticker("\u{1100}\u{1161}"), "\u{ac00}");
+    }
+
+    #[test]
+    fn test_parse_satpoint() {
+        assert_eq!(
+            parse_satpoint("abcd:1:546"),
+            Some(("abcd:1".to_string(), 546))
+        );
+        assert_eq!(parse_satpoint("abcd:0:0"), Some(("abcd:0".to_string(), 0)));
+        assert_eq!(parse_satpoint("abcd:1"), None);
+        assert_eq!(parse_satpoint("abcd:x:0"), None);
+        assert_eq!(parse_satpoint("abcd:1:-1"), None);
+        assert_eq!(parse_satpoint(":1:0"), None);
+    }
+}
@@ -0,0 +1,375 @@
+use crate::indexer::Indexer;
+use crate::models::{Balances, History, Inscriptions, Tracker};
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{OptionalExtension, QueryResult};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct Discrepancy {
+    pub ticker: String,
+    pub address: Option<String>,
+    pub check: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}{}: expected {}, found {}",
+            self.ticker,
+            self.check,
+            self.address
+                .as_ref()
+                .map(|address| format!(" ({})", address))
+                .unwrap_or_default(),
+            self.expected,
+            self.actual
+        )
+    }
+}
+
+#[derive(Default)]
+struct ExpectedBalance {
+    total: BigDecimal,
+    transfer_balance: BigDecimal,
+}
+
+/// Checks a single ticker's stored state against the values recomputed from its history rows
+/// and its valid, unspent transfer inscriptions.
+pub fn verify_ticker(
+    tracker: &Tracker,
+    balances: &[Balances],
+    history: &[History],
+    transfers: &[Inscriptions],
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    let mut discrepancy = |address: Option<&str>, check, expected: String, actual: String| {
+        discrepancies.push(Discrepancy {
+            ticker: tracker.ticker.clone(),
+            address: address.map(str::to_string),
+            check,
+            expected,
+            actual,
+        })
+    };
+
+    let mut expected: BTreeMap<String, ExpectedBalance> = BTreeMap::new();
+    let mut minted = BigDecimal::zero();
+    let mut burned = BigDecimal::zero();
+    let mut sends = 0i64;
+
+    for row in history.iter().filter(|row| !row.invalid) {
+        let sender = row.address_sender.clone().unwrap_or_default();
+        let receiver = row.address_receiver.clone().unwrap_or_default();
+        match row.action.as_str() {
+            "mint" => {
+                minted += row.amount.clone();
+                expected.entry(receiver).or_default().total += row.amount.clone();
+            }
+            "send" => {
+                sends += 1;
+                expected.entry(sender).or_default().total -= row.amount.clone();
+                expected.entry(receiver).or_default().total += row.amount.clone();
+            }
+            "burn" => {
+                sends += 1;
+                burned += row.amount.clone();
+                expected.entry(sender).or_default().total -= row.amount.clone();
+            }
+            _ => {}
+        }
+    }
+
+    for inscription in transfers {
+        expected
+            .entry(inscription.genesis_address.clone())
+            .or_default()
+            .transfer_balance += inscription.amount.clone().unwrap_or_default();
+    }
+
+    let mut stored_total = BigDecimal::zero();
+    let mut holders = 0i64;
+    let mut seen = BTreeSet::new();
+
+    for balance in balances {
+        seen.insert(balance.address.clone());
+        stored_total += balance.balance.clone() + balance.transfer_balance.clone();
+
+        if balance.balance < BigDecimal::zero() || balance.transfer_balance < BigDecimal::zero() {
+            discrepancy(
+                Some(&balance.address),
+                "negative balance",
+                ">= 0".to_string(),
+                format!("{}/{}", balance.balance, balance.transfer_balance),
+            );
+        }
+
+        if balance.balance != BigDecimal::zero() || balance.transfer_balance != BigDecimal::zero() {
+            holders += 1;
+        }
+
+        let (expected_balance, expected_transfer) = expected
+            .get(&balance.address)
+            .map(|e| {
+                (
+                    e.total.clone() - e.transfer_balance.clone(),
+                    e.transfer_balance.clone(),
+                )
+            })
+            .unwrap_or_default();
+
+        if expected_balance != balance.balance {
+            discrepancy(
+                Some(&balance.address),
+                "balance",
+                expected_balance.to_string(),
+                balance.balance.to_string(),
+            );
+        }
+        if expected_transfer != balance.transfer_balance {
+            discrepancy(
+                Some(&balance.address),
+                "transfer_balance",
+                expected_transfer.to_string(),
+                balance.transfer_balance.to_string(),
+            );
+        }
+    }
+
+    for (address, balance) in &expected {
+        if !seen.contains(address)
+            && (balance.total != BigDecimal::zero()
+                || balance.transfer_balance != BigDecimal::zero())
+        {
+            discrepancy(
+                Some(address),
+                "missing balance row",
+                balance.total.to_string(),
+                "none".to_string(),
+            );
+        }
+    }
+
+    let circulating = tracker.supply_minted.clone() - tracker.burned.clone();
+    if stored_total != circulating {
+        discrepancy(
+            None,
+            "sum of balances",
+            circulating.to_string(),
+            stored_total.to_string(),
+        );
+    }
+    if minted != tracker.supply_minted {
+        discrepancy(
+            None,
+            "supply_minted",
+            minted.to_string(),
+            tracker.supply_minted.to_string(),
+        );
+    }
+    if burned != tracker.burned {
+        discrepancy(
+            None,
+            "burned",
+            burned.to_string(),
+            tracker.burned.to_string(),
+        );
+    }
+    if holders != tracker.holders {
+        discrepancy(
+            None,
+            "holders",
+            holders.to_string(),
+            tracker.holders.to_string(),
+        );
+    }
+    if sends != tracker.transactions {
+        discrepancy(
+            None,
+            "transactions",
+            sends.to_string(),
+            tracker.transactions.to_string(),
+        );
+    }
+
+    discrepancies
+}
+
+impl Indexer {
+    /// Recomputes the balance and supply invariants for every ticker (or just `ticker`) and
+    /// returns all discrepancies found.
+    pub(crate) fn verify(&mut self, ticker: Option<String>) -> QueryResult<Vec<Discrepancy>> {
+        let trackers = match ticker {
+            Some(ticker) => self
+                .database
+                .get_tracker(ticker)
+                .optional()?
+                .into_iter()
+                .collect(),
+            None => self.database.get_trackers()?,
+        };
+
+        let mut discrepancies = Vec::new();
+        for tracker in trackers {
+            let balances = self
+                .database
+                .get_balances_by_ticker(tracker.ticker.clone())?;
+            let history = self
+                .database
+                .get_history_by_ticker(tracker.ticker.clone())?;
+            let transfers = self
+                .database
+                .get_unspent_transfer_inscriptions(tracker.ticker.clone())?;
+            discrepancies.extend(verify_ticker(&tracker, &balances, &history, &transfers));
+        }
+        Ok(discrepancies)
+    }
+
+    /// Runs `verify` after the block at `height` when a verification interval is configured,
+    /// recording the outcome in the status table. A verification that cannot run counts as a
+    /// failure.
+    pub(crate) fn verify_at_interval(&mut self, height: i32, interval: i32) {
+        if interval <= 0 || height % interval != 0 {
+            return;
+        }
+
+        let failed = match self.verify(None) {
+            Ok(discrepancies) => {
+                for discrepancy in &discrepancies {
+                    log::error!("Verification failed at block {}: {}", height, discrepancy);
+                }
+                !discrepancies.is_empty()
+            }
+            Err(err) => {
+                log::error!("Verification could not run at block {}: {}", height, err);
+                true
+            }
+        };
+        if let Err(err) = self.record_verification(height, failed) {
+            log::error!("Failed to record verification at block {}: {}", height, err);
+        }
+    }
+
+    fn record_verification(&mut self, height: i32, failed: bool) -> QueryResult<()> {
+        self.database
+            .update_status("last_verify_height".to_string(), height.to_string())?;
+        if failed {
+            let failures = self
+                .database
+                .get_status("verify_failures".to_string())
+                .optional()?
+                .and_then(|status| status.value)
+                .unwrap_or_default()
+                .parse::<i64>()
+                .unwrap_or_default();
+            self.database
+                .update_status("verify_failures".to_string(), (failures + 1).to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Balances, History, Tracker};
+    use crate::verify::verify_ticker;
+    use bigdecimal::{BigDecimal, Zero};
+
+    fn tracker(supply_minted: i64, holders: i64, transactions: i64) -> Tracker {
+        Tracker {
+            ticker: "test".to_string(),
+            supply: BigDecimal::from(1000),
+            supply_minted: BigDecimal::from(supply_minted),
+            limit_mint: BigDecimal::from(100),
+            decimals: 18,
+            holders,
+            transactions,
+            ..Default::default()
+        }
+    }
+
+    fn balance(address: &str, balance: i64) -> Balances {
+        Balances {
+            id: 1,
+            address: address.to_string(),
+            ticker: "test".to_string(),
+            balance: BigDecimal::from(balance),
+            transfer_balance: BigDecimal::zero(),
+        }
+    }
+
+    fn history(action: &str, sender: &str, receiver: &str, amount: i64) -> History {
+        History {
+            id: 1,
+            address_sender: Some(sender.to_string()),
+            address_receiver: Some(receiver.to_string()),
+            amount: BigDecimal::from(amount),
+            ticker: "test".to_string(),
+            action: action.to_string(),
+            invalid: false,
+            inscription_id: "i0".to_string(),
+            inscription_num: 1,
+            tx_id: "tx".to_string(),
+            height: 1,
+            timestamp: 0,
+            amount_requested: BigDecimal::from(amount),
+            partial_mint: false,
+        }
+    }
+
+    #[test]
+    fn test_verify_ticker_consistent() {
+        let history = vec![
+            history("mint", "", "alice", 100),
+            history("transfer", "alice", "", 40),
+            history("send", "alice", "bob", 40),
+        ];
+        let balances = vec![balance("alice", 60), balance("bob", 40)];
+
+        assert!(verify_ticker(&tracker(100, 2, 1), &balances, &history, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_verify_ticker_discrepancies() {
+        let history = vec![history("mint", "", "alice", 100)];
+        let balances = vec![balance("alice", 90), balance("bob", -10)];
+
+        let checks = verify_ticker(&tracker(100, 1, 0), &balances, &history, &[])
+            .into_iter()
+            .map(|discrepancy| discrepancy.check)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            checks,
+            vec![
+                "balance",
+                "negative balance",
+                "balance",
+                "sum of balances",
+                "holders"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_ticker_burns() {
+        let history = vec![
+            history("mint", "", "alice", 100),
+            history("burn", "alice", "", 30),
+        ];
+        let balances = vec![balance("alice", 70)];
+        let mut burned = tracker(100, 1, 1);
+        burned.burned = BigDecimal::from(30);
+
+        assert!(verify_ticker(&burned, &balances, &history, &[]).is_empty());
+
+        let checks = verify_ticker(&tracker(100, 1, 1), &balances, &history, &[])
+            .into_iter()
+            .map(|discrepancy| discrepancy.check)
+            .collect::<Vec<_>>();
+        assert_eq!(checks, vec!["sum of balances", "burned"]);
+    }
+}